@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use crate::base64::encode;
+
+    #[test]
+    fn test_base64_rfc_vectors() {
+        for (input, expected) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(input.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn test_base64_uses_the_whole_alphabet() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let encoded = encode(&bytes);
+        assert_eq!(encoded.len(), 344);
+        assert!(encoded.starts_with("AAECAwQFBgcICQoLDA0ODxAREhM"));
+        assert!(encoded.ends_with("+fr7/P3+/w=="));
+    }
+}
@@ -0,0 +1,259 @@
+use std::io::{Read, Write};
+
+use crate::error::{MiniPngError, Result};
+use crate::limits::DecodeLimits;
+use crate::mininpng::{Header, MiniPNG, Palette};
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+
+const FILE_HEADER_SIZE: usize = 14;
+// BITMAPINFOHEADER, the later versions only add fields after it
+const INFO_HEADER_SIZE: usize = 40;
+// 72 DPI in pixels per meter
+const RESOLUTION: u32 = 2835;
+
+impl MiniPNG {
+    /// Decodes an uncompressed BMP file with 1, 8 or 24 bits per pixel.
+    ///
+    /// 24 bit images become RGB and 8 bit images palette images. 1 bit
+    /// images become black and white when their two colors are black and
+    /// white, and palette images otherwise.
+    pub fn from_bmp<R: Read>(reader: R) -> Result<Self> {
+        Self::from_bmp_with_limits(reader, DecodeLimits::default())
+    }
+
+    /// Same as [`MiniPNG::from_bmp`] with custom limits.
+    pub fn from_bmp_with_limits<R: Read>(mut reader: R, limits: DecodeLimits) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(MiniPngError::Io)?;
+
+        if !bytes.starts_with(b"BM") {
+            return Err(MiniPngError::InvalidBmp("missing BM signature"));
+        }
+        let data_offset = u32_at(&bytes, 10)? as usize;
+        let info_size = u32_at(&bytes, FILE_HEADER_SIZE)? as usize;
+        if info_size < INFO_HEADER_SIZE {
+            return Err(MiniPngError::UnsupportedBmp("OS/2 bitmap headers"));
+        }
+
+        let width = i32_at(&bytes, 18)?;
+        let height = i32_at(&bytes, 22)?;
+        let bit_count = u16_at(&bytes, 28)?;
+        let compression = u32_at(&bytes, 30)?;
+        let colors_used = u32_at(&bytes, 46)?;
+
+        if width < 0 {
+            return Err(MiniPngError::InvalidBmp("negative width"));
+        }
+        if compression != 0 {
+            return Err(MiniPngError::UnsupportedBmp("compression"));
+        }
+        if !matches!(bit_count, 1 | 8 | 24) {
+            return Err(MiniPngError::UnsupportedBmp(
+                "bit count other than 1, 8 or 24",
+            ));
+        }
+
+        let palette = match bit_count {
+            24 => None,
+            _ => {
+                let count = match colors_used {
+                    0 => 1 << bit_count,
+                    count => count.min(1 << bit_count),
+                } as usize;
+                let start = FILE_HEADER_SIZE + info_size;
+                let entries = bytes
+                    .get(start..start + count * 4)
+                    .ok_or(MiniPngError::InvalidBmp("truncated color table"))?;
+                Some(Palette {
+                    // entries are stored as blue, green, red and a reserved byte
+                    colors: entries.chunks(4).map(|e| [e[2], e[1], e[0]]).collect(),
+                })
+            }
+        };
+
+        // a 1 bit image is black and white if its colors are black and white,
+        // `inverted` when index 1 is black
+        let black_and_white = match palette.as_ref().map(|p| p.colors.as_slice()) {
+            Some([[0, 0, 0], [255, 255, 255]]) if bit_count == 1 => Some(false),
+            Some([[255, 255, 255], [0, 0, 0]]) if bit_count == 1 => Some(true),
+            _ => None,
+        };
+
+        let header = Header {
+            width: width as u32,
+            height: height.unsigned_abs(),
+            pixel_type: match (bit_count, black_and_white) {
+                (24, _) => PixelType::RGB,
+                (_, Some(_)) => PixelType::BlackAndWhite,
+                _ => PixelType::Palette,
+            },
+        };
+        limits.check_header(&header)?;
+
+        let stride = usize::try_from(row_stride(header.width, bit_count)).map_err(|_| {
+            MiniPngError::DimensionsOverflow {
+                width: header.width,
+                height: header.height,
+            }
+        })?;
+        let rows = stride
+            .checked_mul(header.height as usize)
+            .and_then(|size| bytes.get(data_offset..)?.get(..size))
+            .ok_or(MiniPngError::InvalidBmp("truncated pixel data"))?;
+
+        let mut image = match black_and_white {
+            Some(_) => MiniPNG::new(header, None)?,
+            None => MiniPNG::new(header, palette)?,
+        };
+        let top_down = height < 0;
+        let (width, height) = (image.header.width, image.header.height);
+
+        for y in 0..height {
+            // rows are stored bottom-up unless the height is negative
+            let stored_row = if top_down { y } else { height - 1 - y };
+            let row = &rows[stored_row as usize * stride..][..stride];
+
+            for x in 0..width {
+                let xs = x as usize;
+                let pixel = match bit_count {
+                    24 => Pixel::Rgb([row[xs * 3 + 2], row[xs * 3 + 1], row[xs * 3]]),
+                    8 => Pixel::Index(row[xs]),
+                    _ => {
+                        let bit = (row[xs / 8] >> (7 - (xs % 8))) & 1;
+                        match black_and_white {
+                            Some(inverted) => Pixel::Bw((bit == 1) != inverted),
+                            None => Pixel::Index(bit),
+                        }
+                    }
+                };
+                image.set_pixel(x, y, pixel)?;
+            }
+        }
+
+        if let Some(palette) = &image.palette
+            && let Some(&index) = image
+                .data
+                .iter()
+                .find(|&&i| i as usize >= palette.colors.len())
+        {
+            return Err(MiniPngError::InvalidPaletteIndex(index));
+        }
+
+        Ok(image)
+    }
+}
+
+impl MiniPNG {
+    /// Encodes the image as an uncompressed bottom-up BMP file.
+    ///
+    /// Black and white images are written with 1 bit per pixel, palette and
+    /// gray level images with 8 bits per pixel and a color table, and RGB
+    /// images with 24 bits per pixel. Comments are dropped as BMP files
+    /// can't store them.
+    pub fn to_bmp<W: Write>(&self, mut writer: W) -> Result<W> {
+        self.header.check_data_size(self.data.len())?;
+
+        let (bit_count, colors) = match self.header.pixel_type {
+            PixelType::BlackAndWhite => (1, vec![[0, 0, 0], [255, 255, 255]]),
+            PixelType::GrayLevels => (8, (0..=255).map(|v| [v, v, v]).collect()),
+            PixelType::Palette => {
+                let palette = self.palette.as_ref().ok_or(MiniPngError::MissingPalette)?;
+                let colors = &palette.colors[..palette.colors.len().min(256)];
+                if let Some(&index) = self.data.iter().find(|&&i| i as usize >= colors.len()) {
+                    return Err(MiniPngError::InvalidPaletteIndex(index));
+                }
+                (8, colors.to_vec())
+            }
+            PixelType::RGB => (24, Vec::new()),
+        };
+
+        let width = bmp_dimension("width", self.header.width)?;
+        let height = bmp_dimension("height", self.header.height)?;
+        let stride = row_stride(self.header.width, bit_count);
+        let data_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE + colors.len() * 4;
+        let image_size = stride * self.header.height as u64;
+        let file_size = data_offset as u64 + image_size;
+        let file_size = u32::try_from(file_size).map_err(|_| MiniPngError::LimitExceeded {
+            limit: "file size",
+            value: file_size,
+            max: u32::MAX as u64,
+        })?;
+        // a row is smaller than the file, which fits in 32 bits
+        let stride = stride as usize;
+
+        let mut headers = Vec::with_capacity(data_offset);
+        headers.extend(b"BM");
+        headers.extend(file_size.to_le_bytes());
+        headers.extend([0; 4]);
+        headers.extend((data_offset as u32).to_le_bytes());
+
+        headers.extend((INFO_HEADER_SIZE as u32).to_le_bytes());
+        headers.extend(width.to_le_bytes());
+        headers.extend(height.to_le_bytes());
+        // one plane
+        headers.extend(1u16.to_le_bytes());
+        headers.extend(bit_count.to_le_bytes());
+        // no compression
+        headers.extend(0u32.to_le_bytes());
+        headers.extend((image_size as u32).to_le_bytes());
+        headers.extend(RESOLUTION.to_le_bytes());
+        headers.extend(RESOLUTION.to_le_bytes());
+        headers.extend((colors.len() as u32).to_le_bytes());
+        headers.extend(0u32.to_le_bytes());
+
+        for [r, g, b] in colors {
+            headers.extend([b, g, r, 0]);
+        }
+        writer.write_all(&headers).map_err(MiniPngError::Io)?;
+
+        let rows: Vec<Vec<Pixel>> = self.rows()?.collect();
+        for row in rows.iter().rev() {
+            let mut bytes = vec![0u8; stride];
+            for (x, pixel) in row.iter().enumerate() {
+                match *pixel {
+                    Pixel::Bw(white) => bytes[x / 8] |= (white as u8) << (7 - (x % 8)),
+                    Pixel::Gray(value) | Pixel::Index(value) => bytes[x] = value,
+                    Pixel::Rgb([r, g, b]) => bytes[x * 3..x * 3 + 3].copy_from_slice(&[b, g, r]),
+                }
+            }
+            writer.write_all(&bytes).map_err(MiniPngError::Io)?;
+        }
+
+        writer.flush().map_err(MiniPngError::Io)?;
+        Ok(writer)
+    }
+}
+
+// size of a stored row, padded to a multiple of 4 bytes
+fn row_stride(width: u32, bit_count: u16) -> u64 {
+    (width as u64 * bit_count as u64).div_ceil(32) * 4
+}
+
+// BMP dimensions are signed
+fn bmp_dimension(name: &'static str, value: u32) -> Result<i32> {
+    i32::try_from(value).map_err(|_| MiniPngError::LimitExceeded {
+        limit: name,
+        value: value as u64,
+        max: i32::MAX as u64,
+    })
+}
+
+fn field<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|field| field.try_into().ok())
+        .ok_or(MiniPngError::InvalidBmp("truncated header"))
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16> {
+    field(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32> {
+    field(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn i32_at(bytes: &[u8], offset: usize) -> Result<i32> {
+    field(bytes, offset).map(i32::from_le_bytes)
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::sample;
+    use crate::{DecodeLimits, MiniPNG, MiniPngError, Palette, Pixel, PixelType};
+
+    // a BMP file with a BITMAPINFOHEADER, the color table given as BGRx
+    // entries and the already padded pixel rows
+    fn bmp(width: i32, height: i32, bit_count: u16, table: &[[u8; 4]], rows: &[u8]) -> Vec<u8> {
+        let data_offset = 54 + table.len() as u32 * 4;
+        let mut bytes = b"BM".to_vec();
+        bytes.extend((data_offset + rows.len() as u32).to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend(data_offset.to_le_bytes());
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(bit_count.to_le_bytes());
+        // compression, image size and resolution, then the number of colors
+        bytes.extend([0; 4 * 4]);
+        bytes.extend((table.len() as u32).to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend(table.as_flattened());
+        bytes.extend(rows);
+        bytes
+    }
+
+    #[test]
+    fn test_bmp_round_trips() {
+        for bytes in [
+            include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+            include_bytes!("../minipng-samples/bw/ok/uneven-dimensions.mp"),
+            include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+            include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp"),
+        ] {
+            let image = sample(bytes);
+            let decoded = MiniPNG::from_bmp(image.to_bmp(Vec::new()).unwrap().as_slice()).unwrap();
+            assert_eq!(decoded.header, image.header);
+            assert_eq!(decoded.palette, image.palette);
+            // compares pixels as the padding bits of the samples are not zero
+            assert!(decoded.pixels().unwrap().eq(image.pixels().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_bmp_gray_levels_become_a_gray_palette() {
+        let image = sample(include_bytes!("../minipng-samples/other/ok/gray.mp"));
+        let decoded = MiniPNG::from_bmp(image.to_bmp(Vec::new()).unwrap().as_slice()).unwrap();
+
+        assert_eq!(decoded.header.pixel_type, PixelType::Palette);
+        assert_eq!(decoded.palette.as_ref().unwrap().colors.len(), 256);
+        assert_eq!(decoded.data, image.data);
+    }
+
+    #[test]
+    fn test_bmp_layout() {
+        let mut image = sample(include_bytes!("../minipng-samples/other/ok/french-flag.mp"));
+        image.comments.clear();
+        let bytes = image.to_bmp(Vec::new()).unwrap();
+
+        // 6 pixels of 3 bytes are padded from 18 to 20 bytes, 4 rows
+        assert_eq!(bytes.len(), 54 + 20 * 4);
+        assert_eq!(bytes[2..6], (54u32 + 80).to_le_bytes());
+        assert_eq!(bytes[28..30], 24u16.to_le_bytes());
+
+        // the last row comes first, in blue, green, red order
+        let last_row = &bytes[54..74];
+        let [r, g, b] = image.color_of(image.get_pixel(0, 3).unwrap()).unwrap();
+        assert_eq!(last_row[..3], [b, g, r]);
+        assert_eq!(last_row[18..], [0, 0]);
+    }
+
+    #[test]
+    fn test_bmp_top_down_and_one_bit_palettes() {
+        let white_black = [[255, 255, 255, 0], [0, 0, 0, 0]];
+        // 3x2 top-down image, 1 is black with this color table
+        let bytes = bmp(3, -2, 1, &white_black, &[0xa0, 0, 0, 0, 0x40, 0, 0, 0]);
+        let image = MiniPNG::from_bmp(bytes.as_slice()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::BlackAndWhite);
+        let pixels: Vec<Pixel> = image.pixels().unwrap().collect();
+        assert_eq!(
+            pixels,
+            [false, true, false, true, false, true].map(Pixel::Bw)
+        );
+
+        // other colors are kept in a palette
+        let red_blue = [[0, 0, 255, 0], [255, 0, 0, 0]];
+        let bytes = bmp(3, 2, 1, &red_blue, &[0xa0, 0, 0, 0, 0x40, 0, 0, 0]);
+        let image = MiniPNG::from_bmp(bytes.as_slice()).unwrap();
+        assert_eq!(
+            image.palette,
+            Some(Palette {
+                colors: vec![[255, 0, 0], [0, 0, 255]]
+            })
+        );
+        // bottom-up: the first stored row is the last one
+        assert_eq!(image.data, vec![0, 1, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_bmp_errors() {
+        let table = [[0, 0, 0, 0], [255, 255, 255, 0]];
+        let valid = bmp(1, 1, 1, &table, &[0, 0, 0, 0]);
+        assert!(MiniPNG::from_bmp(valid.as_slice()).is_ok());
+
+        assert!(matches!(
+            MiniPNG::from_bmp(&valid[..valid.len() - 1]),
+            Err(MiniPngError::InvalidBmp(_))
+        ));
+        assert!(matches!(
+            MiniPNG::from_bmp(&valid[1..]),
+            Err(MiniPngError::InvalidBmp(_))
+        ));
+
+        let mut compressed = valid.clone();
+        compressed[30] = 1;
+        assert!(matches!(
+            MiniPNG::from_bmp(compressed.as_slice()),
+            Err(MiniPngError::UnsupportedBmp(_))
+        ));
+        assert!(matches!(
+            MiniPNG::from_bmp(bmp(1, 1, 16, &[], &[0, 0, 0, 0]).as_slice()),
+            Err(MiniPngError::UnsupportedBmp(_))
+        ));
+
+        // an index past the color table
+        assert!(matches!(
+            MiniPNG::from_bmp(bmp(1, 1, 8, &table, &[2, 0, 0, 0]).as_slice()),
+            Err(MiniPngError::InvalidPaletteIndex(2))
+        ));
+    }
+
+    #[test]
+    fn test_bmp_limits() {
+        let limits = DecodeLimits {
+            max_height: 1,
+            ..DecodeLimits::default()
+        };
+        let table = [[0, 0, 0, 0], [255, 255, 255, 0]];
+        let one_row = bmp(1, 1, 1, &table, &[0; 4]);
+        assert!(MiniPNG::from_bmp_with_limits(one_row.as_slice(), limits.clone()).is_ok());
+
+        // top-down images have a negative height
+        let two_rows = bmp(1, -2, 1, &table, &[0; 8]);
+        assert!(MiniPNG::from_bmp(two_rows.as_slice()).is_ok());
+        assert!(matches!(
+            MiniPNG::from_bmp_with_limits(two_rows.as_slice(), limits),
+            Err(MiniPngError::LimitExceeded {
+                limit: "height",
+                value: 2,
+                max: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::{MiniPngError, Result};
+use crate::limits::{BlockBudget, DecodeLimits};
+use crate::mininpng::{Header, MiniPNG, Palette, check_blocks};
+
+/// A block borrowed from a Mini-PNG file, without any interpretation of its content.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawBlock<'a> {
+    /// Position of the block in the file, starting at 0.
+    pub index: usize,
+    /// Type byte of the block, `H`, `P`, `C` and `D` for the known blocks.
+    pub block_type: u8,
+    /// Offset of the type byte from the start of the file.
+    pub offset: usize,
+    /// Content of the block, without the type and length fields.
+    pub content: &'a [u8],
+}
+
+/// Iterator over the blocks of an in-memory Mini-PNG file.
+///
+/// Only the block framing is checked: a truncated length field or content
+/// is returned as a [`MiniPngError::InBlock`] error and ends the iteration.
+#[derive(Debug, Clone)]
+pub struct RawBlocks<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    index: usize,
+}
+
+impl<'a> RawBlocks<'a> {
+    /// Checks the magic number of `bytes` and iterates over the blocks after it.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        if bytes.len() < 8 {
+            return Err(MiniPngError::FileTooSmall);
+        }
+        if &bytes[0..8] != b"Mini-PNG" {
+            return Err(MiniPngError::InvalidMagicNumber);
+        }
+
+        Ok(RawBlocks {
+            bytes,
+            offset: 8,
+            index: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for RawBlocks<'a> {
+    type Item = Result<RawBlock<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let rest = &self.bytes[offset..];
+        let (&block_type, rest) = rest.split_first()?;
+
+        let index = self.index;
+
+        // whatever happens next this is the last block if it is broken
+        self.offset = self.bytes.len();
+
+        let Some((length, rest)) = rest.split_first_chunk::<4>() else {
+            return Some(Err(MiniPngError::InvalidBlockLength.in_block(
+                index,
+                offset as u64,
+                block_type,
+            )));
+        };
+        let declared = u32::from_be_bytes(*length);
+        let length = declared as usize;
+        if rest.len() < length {
+            let error = MiniPngError::BlockLengthMismatch {
+                declared,
+                remaining: rest.len(),
+            };
+            return Some(Err(error.in_block(index, offset as u64, block_type)));
+        }
+
+        self.offset = offset + 5 + length;
+        self.index += 1;
+        Some(Ok(RawBlock {
+            index,
+            block_type,
+            offset,
+            content: &rest[..length],
+        }))
+    }
+}
+
+/// A Mini-PNG image borrowed from a byte slice.
+///
+/// Parsing performs the same checks as the [`MiniPNG`] parser without
+/// allocating: comments and data blocks stay in the original buffer and are
+/// walked again on demand.
+#[derive(Debug, Clone)]
+pub struct MiniPngRef<'a> {
+    /// Content of the `H` block.
+    pub header: Header,
+    /// Raw content of the `P` block, see [`MiniPngRef::palette_colors`].
+    pub palette: Option<&'a [u8]>,
+    bytes: &'a [u8],
+    data_size: usize,
+}
+
+impl<'a> MiniPngRef<'a> {
+    /// Parses a whole Mini-PNG file with the default [`DecodeLimits`].
+    pub fn parse(bytes: &'a [u8]) -> Result<Self> {
+        Self::parse_with_limits(bytes, DecodeLimits::default())
+    }
+
+    /// Same as [`MiniPngRef::parse`] with custom limits.
+    pub fn parse_with_limits(bytes: &'a [u8], limits: DecodeLimits) -> Result<Self> {
+        let mut budget = BlockBudget::new(limits);
+        let mut header: Option<Header> = None;
+        let mut palette: Option<&'a [u8]> = None;
+        let mut data_size = 0usize;
+
+        for block in RawBlocks::new(bytes)? {
+            let block = block?;
+            let in_block =
+                |e: MiniPngError| e.in_block(block.index, block.offset as u64, block.block_type);
+            budget
+                .spend(block.block_type, block.content.len() as u32)
+                .map_err(in_block)?;
+
+            match block.block_type {
+                b'C' => {
+                    // this allows for utf8 encoded comments which is not explicitly supported by
+                    // the spec which refers to ascii encoded comments
+                    std::str::from_utf8(block.content)
+                        .map_err(|_| in_block(MiniPngError::InvalidUtf8Comment))?;
+                }
+
+                b'D' => {
+                    data_size += block.content.len();
+                }
+
+                b'H' => {
+                    if header.is_some() {
+                        return Err(in_block(MiniPngError::DuplicateHeader));
+                    }
+                    let parsed = Header::parse(block.content).map_err(in_block)?;
+                    budget.limits.check_header(&parsed).map_err(in_block)?;
+                    header = Some(parsed);
+                }
+
+                b'P' => {
+                    if palette.is_some() {
+                        return Err(in_block(MiniPngError::DuplicatePalette));
+                    }
+                    palette = Some(block.content);
+                }
+
+                block_type => {
+                    return Err(in_block(MiniPngError::InvalidBlockType(block_type as char)));
+                }
+            }
+        }
+
+        let header = check_blocks(header, palette.is_some(), data_size)?;
+
+        Ok(MiniPngRef {
+            header,
+            palette,
+            bytes,
+            data_size,
+        })
+    }
+
+    /// Colors of the palette, trailing bytes that don't form a whole color are ignored.
+    pub fn palette_colors(&self) -> impl Iterator<Item = [u8; 3]> + 'a {
+        self.palette
+            .unwrap_or_default()
+            .chunks_exact(3)
+            .map(|chunk| [chunk[0], chunk[1], chunk[2]])
+    }
+
+    /// Comments in file order.
+    pub fn comments(&self) -> impl Iterator<Item = &'a str> + 'a {
+        // comments were checked to be valid UTF-8 by `parse`
+        self.blocks_of_type(b'C')
+            .filter_map(|content| std::str::from_utf8(content).ok())
+    }
+
+    /// Content of every `D` block in file order.
+    pub fn data_blocks(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.blocks_of_type(b'D')
+    }
+
+    /// Pixel data, lazily concatenated from the `D` blocks.
+    pub fn data(&self) -> impl Iterator<Item = u8> + 'a {
+        self.data_blocks().flatten().copied()
+    }
+
+    /// Total size of the pixel data in bytes.
+    pub fn data_size(&self) -> usize {
+        self.data_size
+    }
+
+    /// Copies the image into an owned [`MiniPNG`].
+    pub fn to_minipng(&self) -> MiniPNG {
+        let mut data = Vec::with_capacity(self.data_size);
+        for block in self.data_blocks() {
+            data.extend_from_slice(block);
+        }
+
+        MiniPNG {
+            header: self.header.clone(),
+            palette: self.palette.map(Palette::parse),
+            data,
+            comments: self.comments().map(str::to_string).collect(),
+        }
+    }
+
+    fn blocks_of_type(&self, block_type: u8) -> impl Iterator<Item = &'a [u8]> + 'a {
+        // the framing was checked by `parse` so no error can show up here
+        RawBlocks {
+            bytes: self.bytes,
+            offset: 8,
+            index: 0,
+        }
+        .filter_map(Result::ok)
+        .filter(move |block| block.block_type == block_type)
+        .map(|block| block.content)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for MiniPngRef<'a> {
+    type Error = MiniPngError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self> {
+        MiniPngRef::parse(bytes)
+    }
+}
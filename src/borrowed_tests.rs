@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use crate::{MiniPNG, MiniPngError, MiniPngRef, PixelType, RawBlocks};
+
+    #[test]
+    fn test_ref_borrows_comments_and_data() {
+        let bytes = include_bytes!("../minipng-samples/bw/ok/split-black.mp");
+        let image = MiniPngRef::parse(bytes).unwrap();
+
+        assert_eq!(image.header.width, 4);
+        assert_eq!(image.header.pixel_type, PixelType::BlackAndWhite);
+
+        let comment = image.comments().next().unwrap();
+        assert!(bytes.as_ptr_range().contains(&comment.as_ptr()));
+
+        let owned: MiniPNG = bytes.to_vec().try_into().unwrap();
+        assert_eq!(image.data_size(), owned.data.len());
+        assert_eq!(image.data().collect::<Vec<_>>(), owned.data);
+        assert_eq!(image.comments().collect::<Vec<_>>(), owned.comments);
+    }
+
+    #[test]
+    fn test_ref_palette() {
+        let bytes = include_bytes!("../minipng-samples/other/ok/french-palette.mp");
+        let image = MiniPngRef::parse(bytes).unwrap();
+
+        assert_eq!(
+            image.palette_colors().collect::<Vec<_>>(),
+            vec![[0, 0, 255], [255, 255, 255], [255, 0, 0]]
+        );
+
+        let owned = image.to_minipng();
+        assert_eq!(owned.palette.unwrap().colors.len(), 3);
+        assert_eq!(owned.data.len(), 24);
+    }
+
+    #[test]
+    fn test_ref_same_errors_as_owned_parser() {
+        let missing_header = include_bytes!("../minipng-samples/bw/nok/missing-header.mp");
+        assert!(matches!(
+            MiniPngRef::parse(missing_header),
+            Err(MiniPngError::MissingHeader)
+        ));
+
+        let broken = include_bytes!("../minipng-samples/bw/nok/broken-dimensions2.mp");
+        assert!(matches!(
+            MiniPngRef::parse(broken),
+            Err(MiniPngError::DataSizeMismatch { .. })
+        ));
+
+        let mut truncated = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        truncated.pop();
+        let error = MiniPngRef::parse(&truncated).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::BlockLengthMismatch {
+                declared: 10,
+                remaining: 9
+            }
+        ));
+    }
+
+    #[test]
+    fn test_raw_blocks_offsets() {
+        let bytes = include_bytes!("../minipng-samples/bw/ok/A.mp");
+        let blocks: Vec<_> = RawBlocks::new(bytes)
+            .unwrap()
+            .map(|block| {
+                let block = block.unwrap();
+                (block.block_type, block.offset, block.content.len())
+            })
+            .collect();
+
+        assert_eq!(blocks, vec![(b'H', 8, 9), (b'C', 22, 11), (b'D', 38, 10)]);
+    }
+}
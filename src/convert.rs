@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dither::{self, Dither};
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::{Header, MiniPNG, Palette};
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+use crate::quantize::{Quantizer, median_cut, octree};
+
+/// Options of [`MiniPNG::convert`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertOptions {
+    /// Gray level from which a pixel becomes white when converting to black and white.
+    pub threshold: u8,
+    /// How the palette is built when converting to a palette image.
+    pub quantizer: Quantizer,
+    /// Maximum size of the palette built when converting to a palette image.
+    pub max_colors: usize,
+    /// Dithering applied when converting to black and white or to a palette.
+    pub dither: Dither,
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        ConvertOptions {
+            threshold: 128,
+            quantizer: Quantizer::Exact,
+            max_colors: 256,
+            dither: Dither::None,
+        }
+    }
+}
+
+/// Perceived brightness of a color, using the ITU-R BT.601 weights.
+pub fn luminance([r, g, b]: [u8; 3]) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+impl MiniPNG {
+    /// Converts the image to another pixel type.
+    ///
+    /// Colors are turned into gray levels with [`luminance`], then into
+    /// black and white with `options.threshold`.
+    ///
+    /// Converting to a palette keeps exactly the colors of the image, in
+    /// order of appearance, when there are at most `options.max_colors` of
+    /// them. Otherwise [`Quantizer::Exact`] fails with
+    /// [`MiniPngError::TooManyColors`] while the other quantizers reduce the
+    /// colors and map each pixel to the nearest palette entry.
+    ///
+    /// With `options.dither`, the error made on each pixel when converting to
+    /// black and white or to a palette is spread over its neighbours.
+    ///
+    /// An image that already has the `target` pixel type is returned as is,
+    /// unless it is a palette image with more than `options.max_colors`
+    /// colors, whose palette is built again.
+    ///
+    /// Comments are kept.
+    pub fn convert(&self, target: PixelType, options: &ConvertOptions) -> Result<MiniPNG> {
+        let palette_size = self
+            .palette
+            .as_ref()
+            .map_or(0, |palette| palette.colors.len());
+        if target == self.header.pixel_type
+            && (target != PixelType::Palette || palette_size <= options.max_colors.clamp(1, 256))
+        {
+            return Ok(self.clone());
+        }
+
+        let colors: Vec<[u8; 3]> = self
+            .pixels()?
+            .map(|pixel| self.color_of(pixel))
+            .collect::<Result<_>>()?;
+
+        let header = Header {
+            pixel_type: target,
+            ..self.header.clone()
+        };
+        let palette = match target {
+            PixelType::Palette => Some(build_palette(&colors, options)?),
+            _ => None,
+        };
+        let width = self.header.width as usize;
+        let pixels = match (target, &palette) {
+            (PixelType::BlackAndWhite, _) => {
+                dither::apply(&colors, width, options.dither, 255, |color| {
+                    match luminance(color) >= options.threshold {
+                        true => (Pixel::Bw(true), [255; 3]),
+                        false => (Pixel::Bw(false), [0; 3]),
+                    }
+                })
+            }
+            (PixelType::Palette, Some(palette)) => {
+                // ordered dithering moves each channel by about the distance
+                // between two palette colors
+                let levels = (palette.colors.len() as f64).cbrt().max(2.0);
+                let spread = (255.0 / (levels - 1.0)) as i32;
+                let mut indexes = HashMap::new();
+
+                dither::apply(&colors, width, options.dither, spread, |color| {
+                    let index = *indexes
+                        .entry(color)
+                        .or_insert_with(|| palette.nearest(color).unwrap_or(0));
+                    let shown = palette.colors.get(index as usize).copied();
+                    (Pixel::Index(index), shown.unwrap_or(color))
+                })
+            }
+            _ => colors
+                .iter()
+                .map(|&color| match target {
+                    PixelType::GrayLevels => Pixel::Gray(luminance(color)),
+                    _ => Pixel::Rgb(color),
+                })
+                .collect(),
+        };
+
+        let mut image = MiniPNG::new(header, palette)?;
+        image.comments = self.comments.clone();
+
+        for (i, pixel) in pixels.into_iter().enumerate() {
+            image.set_pixel((i % width) as u32, (i / width) as u32, pixel)?;
+        }
+
+        Ok(image)
+    }
+}
+
+fn build_palette(colors: &[[u8; 3]], options: &ConvertOptions) -> Result<Palette> {
+    let max_colors = options.max_colors.clamp(1, 256);
+    if let Some(palette) = exact_palette(colors, max_colors) {
+        return Ok(palette);
+    }
+
+    match options.quantizer {
+        Quantizer::Exact => Err(MiniPngError::TooManyColors(
+            colors.iter().collect::<HashSet<_>>().len(),
+        )),
+        Quantizer::MedianCut => Ok(median_cut(colors, max_colors)),
+        Quantizer::Octree => Ok(octree(colors, max_colors)),
+    }
+}
+
+// builds a palette of every distinct color, if there are at most `max_colors`
+fn exact_palette(colors: &[[u8; 3]], max_colors: usize) -> Option<Palette> {
+    let mut palette = Palette { colors: Vec::new() };
+    let mut seen = HashSet::new();
+
+    for &color in colors {
+        if seen.insert(color) {
+            if palette.colors.len() == max_colors {
+                return None;
+            }
+            palette.colors.push(color);
+        }
+    }
+
+    Some(palette)
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::sample;
+    use crate::{
+        ConvertOptions, Header, MiniPNG, MiniPngError, Pixel, PixelType, Quantizer, luminance,
+    };
+
+    fn round_trip(image: &MiniPNG) -> MiniPNG {
+        let bytes = image.write_to(Vec::new()).unwrap();
+        MiniPNG::read_from(bytes.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_luminance() {
+        assert_eq!(luminance([0, 0, 0]), 0);
+        assert_eq!(luminance([255, 255, 255]), 255);
+        assert_eq!(luminance([255, 0, 0]), 76);
+        assert_eq!(luminance([0, 0, 255]), 29);
+        assert_eq!(luminance([42, 42, 42]), 42);
+    }
+
+    #[test]
+    fn test_convert_rgb_to_gray_and_black_and_white() {
+        let flag = sample(include_bytes!("../minipng-samples/other/ok/french-flag.mp"));
+
+        let gray = flag
+            .convert(PixelType::GrayLevels, &ConvertOptions::default())
+            .unwrap();
+        assert_eq!(gray.header.pixel_type, PixelType::GrayLevels);
+        assert_eq!(&gray.data[..6], &[29, 29, 255, 255, 76, 76]);
+
+        let bw = gray
+            .convert(
+                PixelType::BlackAndWhite,
+                &ConvertOptions {
+                    threshold: 50,
+                    ..ConvertOptions::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(bw.get_pixel(0, 0).unwrap(), Pixel::Bw(false));
+        assert_eq!(bw.get_pixel(3, 0).unwrap(), Pixel::Bw(true));
+        assert_eq!(bw.get_pixel(5, 3).unwrap(), Pixel::Bw(true));
+        assert_eq!(round_trip(&bw).data, bw.data);
+    }
+
+    #[test]
+    fn test_convert_palette_to_rgb_and_back() {
+        let indexed = sample(include_bytes!(
+            "../minipng-samples/other/ok/german-flag-palette.mp"
+        ));
+
+        let rgb = indexed
+            .convert(PixelType::RGB, &ConvertOptions::default())
+            .unwrap();
+        assert!(rgb.palette.is_none());
+        assert_eq!(rgb.get_pixel(0, 0).unwrap(), Pixel::Rgb([0, 0, 0]));
+        assert_eq!(rgb.get_pixel(0, 14).unwrap(), Pixel::Rgb([255, 255, 0]));
+
+        let back = round_trip(
+            &rgb.convert(PixelType::Palette, &ConvertOptions::default())
+                .unwrap(),
+        );
+        assert_eq!(back.palette, indexed.palette);
+        assert_eq!(back.data, indexed.data);
+    }
+
+    #[test]
+    fn test_convert_black_and_white_to_palette() {
+        let bw = MiniPNG::bw_from_string(" X\nX ").unwrap();
+        let indexed = bw
+            .convert(PixelType::Palette, &ConvertOptions::default())
+            .unwrap();
+
+        assert_eq!(
+            indexed.palette.as_ref().unwrap().colors,
+            vec![[0, 0, 0], [255, 255, 255]]
+        );
+        assert_eq!(indexed.data, vec![0, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_convert_too_many_colors() {
+        let header = Header {
+            width: 300,
+            height: 1,
+            pixel_type: PixelType::RGB,
+        };
+        let mut rgb = MiniPNG::new(header, None).unwrap();
+        for x in 0..300 {
+            rgb.set_pixel(x, 0, Pixel::Rgb([(x % 256) as u8, (x / 256) as u8, 0]))
+                .unwrap();
+        }
+
+        assert!(matches!(
+            rgb.convert(PixelType::Palette, &ConvertOptions::default()),
+            Err(MiniPngError::TooManyColors(300))
+        ));
+    }
+
+    #[test]
+    fn test_convert_palette_to_fewer_colors() {
+        let indexed = sample(include_bytes!(
+            "../minipng-samples/other/ok/german-flag-palette.mp"
+        ));
+        assert_eq!(indexed.palette.as_ref().unwrap().colors.len(), 3);
+
+        // a palette that already fits is kept
+        let same = indexed
+            .convert(PixelType::Palette, &ConvertOptions::default())
+            .unwrap();
+        assert_eq!(same.palette, indexed.palette);
+        assert_eq!(same.data, indexed.data);
+
+        let options = ConvertOptions {
+            max_colors: 2,
+            ..ConvertOptions::default()
+        };
+        assert!(matches!(
+            indexed.convert(PixelType::Palette, &options),
+            Err(MiniPngError::TooManyColors(3))
+        ));
+
+        let reduced = indexed
+            .convert(
+                PixelType::Palette,
+                &ConvertOptions {
+                    quantizer: Quantizer::MedianCut,
+                    ..options
+                },
+            )
+            .unwrap();
+        assert_eq!(reduced.palette.as_ref().unwrap().colors.len(), 2);
+        assert!(reduced.data.iter().all(|&index| index < 2));
+    }
+}
@@ -0,0 +1,129 @@
+/// Dithering used by [`MiniPNG::convert`](crate::MiniPNG::convert) when
+/// reducing an image to black and white or to a palette.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dither {
+    /// Each pixel is mapped to the closest available color.
+    #[default]
+    None,
+    /// Error diffusion to 4 neighbours, spreading the whole error.
+    FloydSteinberg,
+    /// Error diffusion to 6 neighbours, spreading 3/4 of the error.
+    Atkinson,
+    /// Ordered dithering with a 2x2 Bayer matrix.
+    Bayer2,
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    Bayer4,
+    /// Ordered dithering with a 8x8 Bayer matrix.
+    Bayer8,
+}
+
+// (dx, dy, weight) of each neighbour receiving a part of the error, the
+// weights are divided by the given divisor
+const FLOYD_STEINBERG: (&[(isize, usize, i32)], i32) =
+    (&[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)], 16);
+const ATKINSON: (&[(isize, usize, i32)], i32) = (
+    &[
+        (1, 0, 1),
+        (2, 0, 1),
+        (-1, 1, 1),
+        (0, 1, 1),
+        (1, 1, 1),
+        (0, 2, 1),
+    ],
+    8,
+);
+
+// value of the Bayer matrix of size `1 << order` at (x, y), recursively
+// built from the 2x2 matrix
+fn bayer(order: u32, x: usize, y: usize) -> u32 {
+    let mut value = 0;
+    for bit in 0..order {
+        let (bx, by) = ((x >> bit) & 1, (y >> bit) & 1);
+        // 2x2 matrix: [[0, 2], [3, 1]]
+        let cell = match (bx, by) {
+            (0, 0) => 0,
+            (1, 0) => 2,
+            (0, _) => 3,
+            _ => 1,
+        };
+        value |= cell << (2 * (order - 1 - bit));
+    }
+    value
+}
+
+// maps every color to an output value with `quantize`, which also returns
+// the color actually displayed for that value. `spread` is the distance
+// between two output levels on a channel, it scales ordered dithering.
+pub(crate) fn apply<T>(
+    colors: &[[u8; 3]],
+    width: usize,
+    dither: Dither,
+    spread: i32,
+    mut quantize: impl FnMut([u8; 3]) -> (T, [u8; 3]),
+) -> Vec<T> {
+    let kernel = match dither {
+        Dither::None => return colors.iter().map(|&color| quantize(color).0).collect(),
+        Dither::FloydSteinberg => FLOYD_STEINBERG,
+        Dither::Atkinson => ATKINSON,
+        Dither::Bayer2 => return ordered(colors, width, 1, spread, quantize),
+        Dither::Bayer4 => return ordered(colors, width, 2, spread, quantize),
+        Dither::Bayer8 => return ordered(colors, width, 3, spread, quantize),
+    };
+
+    let width = width.max(1);
+    let mut errors = vec![[0i32; 3]; colors.len()];
+    let mut output = Vec::with_capacity(colors.len());
+
+    for (i, color) in colors.iter().enumerate() {
+        let (x, y) = (i % width, i / width);
+        let mut wanted = [0u8; 3];
+        for c in 0..3 {
+            wanted[c] = (color[c] as i32 + errors[i][c]).clamp(0, 255) as u8;
+        }
+
+        let (value, shown) = quantize(wanted);
+        output.push(value);
+
+        let (neighbours, divisor) = kernel;
+        for &(dx, dy, weight) in neighbours {
+            let nx = x as isize + dx;
+            if nx < 0 || nx as usize >= width {
+                continue;
+            }
+            let j = (y + dy) * width + nx as usize;
+            if j >= colors.len() {
+                continue;
+            }
+            for c in 0..3 {
+                errors[j][c] += (wanted[c] as i32 - shown[c] as i32) * weight / divisor;
+            }
+        }
+    }
+
+    output
+}
+
+fn ordered<T>(
+    colors: &[[u8; 3]],
+    width: usize,
+    order: u32,
+    spread: i32,
+    mut quantize: impl FnMut([u8; 3]) -> (T, [u8; 3]),
+) -> Vec<T> {
+    let width = width.max(1);
+    let size = 1usize << order;
+    let cells = (size * size) as i32;
+
+    colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+            let (x, y) = (i % width, i / width);
+            // offset in [-spread / 2, spread / 2[ centered on the cell
+            let cell = bayer(order, x % size, y % size) as i32;
+            let offset = ((2 * cell + 1) * spread) / (2 * cells) - spread / 2;
+            let wanted = color.map(|channel| (channel as i32 + offset).clamp(0, 255) as u8);
+            quantize(wanted).0
+        })
+        .collect()
+}
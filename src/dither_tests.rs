@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConvertOptions, Dither, Header, MiniPNG, Pixel, PixelType, Quantizer, dither::apply,
+    };
+
+    fn gray(width: u32, height: u32, value: u8) -> MiniPNG {
+        let mut image = MiniPNG::new(
+            Header {
+                width,
+                height,
+                pixel_type: PixelType::GrayLevels,
+            },
+            None,
+        )
+        .unwrap();
+        image.data.fill(value);
+        image
+    }
+
+    fn white_count(image: &MiniPNG) -> usize {
+        image
+            .pixels()
+            .unwrap()
+            .filter(|&pixel| pixel == Pixel::Bw(true))
+            .count()
+    }
+
+    fn to_bw(image: &MiniPNG, dither: Dither) -> MiniPNG {
+        image
+            .convert(
+                PixelType::BlackAndWhite,
+                &ConvertOptions {
+                    dither,
+                    ..ConvertOptions::default()
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_no_dither_thresholds() {
+        let image = gray(8, 8, 100);
+        assert_eq!(white_count(&to_bw(&image, Dither::None)), 0);
+    }
+
+    #[test]
+    fn test_dithering_keeps_average_brightness() {
+        // a 50% gray is rendered with about half of the pixels white
+        let image = gray(16, 16, 128);
+        for dither in [
+            Dither::FloydSteinberg,
+            Dither::Bayer2,
+            Dither::Bayer4,
+            Dither::Bayer8,
+        ] {
+            let white = white_count(&to_bw(&image, dither));
+            assert!((120..=136).contains(&white), "{:?}: {}", dither, white);
+        }
+
+        // Atkinson drops a quarter of the error, but still mixes both colors
+        let white = white_count(&to_bw(&image, Dither::Atkinson));
+        assert!((64..=192).contains(&white), "{}", white);
+    }
+
+    #[test]
+    fn test_extremes_are_not_dithered() {
+        for dither in [Dither::FloydSteinberg, Dither::Atkinson, Dither::Bayer8] {
+            assert_eq!(white_count(&to_bw(&gray(8, 8, 0), dither)), 0);
+            assert_eq!(white_count(&to_bw(&gray(8, 8, 255), dither)), 64);
+        }
+    }
+
+    #[test]
+    fn test_bayer2_pattern() {
+        let image = to_bw(&gray(4, 2, 128), Dither::Bayer2);
+        let rows: Vec<Vec<Pixel>> = image.rows().unwrap().collect();
+        assert_eq!(rows[0][0], rows[0][2]);
+        assert_eq!(rows[0][0], rows[1][1]);
+        assert_ne!(rows[0][0], rows[0][1]);
+        assert_ne!(rows[0][0], rows[1][0]);
+    }
+
+    #[test]
+    fn test_floyd_steinberg_spreads_error() {
+        let colors = [[100, 100, 100]; 4];
+        let output = apply(&colors, 2, Dither::FloydSteinberg, 255, |color| {
+            (color[0], [0; 3])
+        });
+        // the first pixel receives no error, its right neighbour 7/16 of 100
+        assert_eq!(output, vec![100, 143, 157, 218]);
+    }
+
+    #[test]
+    fn test_dither_to_palette() {
+        let mut image = MiniPNG::new(
+            Header {
+                width: 16,
+                height: 16,
+                pixel_type: PixelType::RGB,
+            },
+            None,
+        )
+        .unwrap();
+        for y in 0..16 {
+            for x in 0..16 {
+                let value = (x * 16 + y) as u8;
+                image
+                    .set_pixel(x, y, Pixel::Rgb([value, 0, 255 - value]))
+                    .unwrap();
+            }
+        }
+
+        for dither in [Dither::None, Dither::FloydSteinberg, Dither::Bayer4] {
+            let converted = image
+                .convert(
+                    PixelType::Palette,
+                    &ConvertOptions {
+                        quantizer: Quantizer::MedianCut,
+                        max_colors: 4,
+                        dither,
+                        ..ConvertOptions::default()
+                    },
+                )
+                .unwrap();
+            let palette = converted.palette.as_ref().unwrap();
+            assert!(palette.colors.len() <= 4);
+            assert!(converted.pixels().unwrap().all(|pixel| match pixel {
+                Pixel::Index(index) => (index as usize) < palette.colors.len(),
+                _ => false,
+            }));
+        }
+    }
+}
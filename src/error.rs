@@ -1,77 +1,273 @@
 use std::io;
 use thiserror::Error;
 
+use crate::pixel_type::PixelType;
+
+/// Every error that can be raised while reading, writing or displaying a Mini-PNG image.
 #[derive(Error, Debug)]
 pub enum MiniPngError {
+    /// The file is shorter than the magic number.
     #[error("File is too small to be a Mini-PNG image")]
     FileTooSmall,
 
+    /// The file doesn't start with `Mini-PNG`.
     #[error("File doesn't have the magic number \"Mini-PNG\"")]
     InvalidMagicNumber,
 
+    /// A block is cut before the end of its length field.
     #[error("Could not read the length field of the current block")]
     InvalidBlockLength,
 
-    #[error("Block length is invalid")]
-    BlockLengthMismatch,
+    /// A block declares more content than the file holds.
+    #[error("declared length {declared} but only {remaining} bytes remain")]
+    BlockLengthMismatch {
+        /// Length read from the block.
+        declared: u32,
+        /// Bytes left in the file after the length field.
+        remaining: usize,
+    },
 
+    /// A comment block isn't valid UTF-8.
     #[error("Could not read UTF-8 text from a Comment")]
     InvalidUtf8Comment,
 
+    /// The file has more than one header block.
     #[error("Found a second header block")]
     DuplicateHeader,
 
+    /// The header block is shorter than 9 bytes.
     #[error("Header block too small")]
     HeaderTooSmall,
 
+    /// The file has more than one palette block.
     #[error("Found a second palette block")]
     DuplicatePalette,
 
+    /// A block type other than `H`, `P`, `C` and `D`.
     #[error("Invalid block type found: {0}")]
     InvalidBlockType(char),
 
+    /// The file has no header block.
     #[error("No header block found")]
     MissingHeader,
 
+    /// A palette image has no palette block.
     #[error("No palette block found")]
     MissingPalette,
 
+    /// The file has no data block.
     #[error("No data block found")]
     MissingData,
 
+    /// A palette block in an image of another pixel type.
     #[error("A palette block was found but pixel type is not palette")]
     UnexpectedPalette,
 
+    /// A pixel refers to a color outside of the palette.
     #[error("Invalid palette index: {0}")]
     InvalidPaletteIndex(u8),
 
+    /// The pixel data doesn't match the size of the image.
     #[error("Expected {expected} bits ({width}x{height} pixels) but found {found} bits in data")]
     DataSizeMismatch {
+        /// Bits needed by the pixels.
         expected: usize,
+        /// Bits of the data blocks.
         found: usize,
+        /// Width of the image.
         width: u32,
+        /// Height of the image.
         height: u32,
     },
 
+    /// A streamed image received a wrong number of rows.
+    #[error("Expected {expected} rows but {found} were written")]
+    RowCountMismatch {
+        /// Height of the image.
+        expected: u32,
+        /// Rows written.
+        found: u64,
+    },
+
+    /// A streamed row has a wrong number of bytes.
+    #[error("Expected a row of {expected} bytes but found {found} bytes")]
+    RowLengthMismatch {
+        /// Bytes of a row of the image.
+        expected: usize,
+        /// Bytes of the row written.
+        found: usize,
+    },
+
+    /// A block content longer than a `u32` length can declare.
+    #[error("Block content of {0} bytes doesn't fit in a block length")]
+    BlockTooLarge(usize),
+
+    /// A value goes over one of the [`DecodeLimits`](crate::DecodeLimits).
+    #[error("Decoding limit exceeded: {limit} is {value} but at most {max} is allowed")]
+    LimitExceeded {
+        /// Name of the limit.
+        limit: &'static str,
+        /// Value found in the file.
+        value: u64,
+        /// Largest value allowed.
+        max: u64,
+    },
+
+    /// The pixel count of the image doesn't fit in memory.
+    #[error("Image dimensions {width}x{height} overflow the addressable memory")]
+    DimensionsOverflow {
+        /// Width of the image.
+        width: u32,
+        /// Height of the image.
+        height: u32,
+    },
+
+    /// Pixel coordinates outside of the image.
+    #[error("Pixel ({x}, {y}) is outside of the {width}x{height} image")]
+    PixelOutOfBounds {
+        /// Column of the pixel.
+        x: u32,
+        /// Row of the pixel.
+        y: u32,
+        /// Width of the image.
+        width: u32,
+        /// Height of the image.
+        height: u32,
+    },
+
+    /// A pixel of another type than the image.
+    #[error("Expected a pixel of type {expected} but found {found}")]
+    PixelTypeMismatch {
+        /// Pixel type of the image.
+        expected: PixelType,
+        /// Pixel type of the pixel.
+        found: PixelType,
+    },
+
+    /// An image with more colors than a palette or a text art legend holds.
+    #[error("Image has {0} colors, which is more than the palette can hold")]
+    TooManyColors(usize),
+
+    /// A malformed Netpbm file.
+    #[error("Invalid Netpbm file: {0}")]
+    InvalidPnm(&'static str),
+
+    /// A malformed PNG file.
+    #[error("Invalid PNG file: {0}")]
+    InvalidPng(&'static str),
+
+    /// A PNG chunk, named by its type, whose checksum is wrong.
+    #[error("CRC mismatch in PNG chunk {0}")]
+    PngCrcMismatch(String),
+
+    /// A valid PNG file using a feature that isn't supported.
+    #[error("Unsupported PNG image: {0}")]
+    UnsupportedPng(&'static str),
+
+    /// A malformed BMP file.
+    #[error("Invalid BMP file: {0}")]
+    InvalidBmp(&'static str),
+
+    /// A valid BMP file using a feature that isn't supported.
+    #[error("Unsupported BMP image: {0}")]
+    UnsupportedBmp(&'static str),
+
+    /// A malformed zlib stream in a PNG file.
+    #[error("Invalid zlib stream: {0}")]
+    InvalidDeflate(&'static str),
+
+    /// An image format or file extension that isn't supported.
+    #[error("Unsupported image format: {0}")]
+    UnsupportedFormat(String),
+
+    /// Malformed text art.
+    #[error("Invalid text art at line {line}: {reason}")]
+    InvalidTextArt {
+        /// Line of the error, starting at 1.
+        line: usize,
+        /// What is wrong with the line.
+        reason: &'static str,
+    },
+
+    /// A pixel type byte other than 0 to 3.
     #[error("Invalid pixel type: {0}")]
     InvalidPixelType(u8),
 
+    /// A character that text art doesn't map to a pixel.
     #[error("Illegal character found: {0}")]
     IllegalCharacter(char),
 
+    /// An error located in a block of the file.
+    #[error("block #{index} ('{}') at offset {offset:#x}: {source}", *block_type as char)]
+    InBlock {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the block type byte in the file.
+        offset: u64,
+        /// Type byte of the block.
+        block_type: u8,
+        /// The error raised in the block.
+        #[source]
+        source: Box<MiniPngError>,
+    },
+
+    /// An I/O error of the underlying reader or writer.
+    #[error("I/O error: {0}")]
+    Io(#[source] io::Error),
+
+    /// Some of the files given to the validator are invalid.
+    #[error("{failed} of {total} files failed validation")]
+    ValidationFailed {
+        /// Number of invalid files.
+        failed: usize,
+        /// Number of files validated.
+        total: usize,
+    },
+
+    /// A file couldn't be read.
     #[error("Failed to read file '{path}': {source}")]
     FileRead {
+        /// Path of the file.
         path: String,
+        /// The error of the read.
         #[source]
         source: io::Error,
     },
 
+    /// A file couldn't be written.
     #[error("Failed to write file '{path}': {source}")]
     FileWrite {
+        /// Path of the file.
         path: String,
+        /// The error of the write.
         #[source]
         source: io::Error,
     },
 }
 
+impl MiniPngError {
+    /// Returns the error without the block location added by the parsers.
+    pub fn root_cause(&self) -> &MiniPngError {
+        match self {
+            MiniPngError::InBlock { source, .. } => source.root_cause(),
+            e => e,
+        }
+    }
+
+    // attaches the location of the block being parsed to an error raised
+    // while reading it
+    pub(crate) fn in_block(self, index: usize, offset: u64, block_type: u8) -> Self {
+        match self {
+            MiniPngError::Io(_) | MiniPngError::InBlock { .. } => self,
+            e => MiniPngError::InBlock {
+                index,
+                offset,
+                block_type,
+                source: Box::new(e),
+            },
+        }
+    }
+}
+
+/// Result type used across the crate.
 pub type Result<T> = std::result::Result<T, MiniPngError>;
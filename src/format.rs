@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use crate::error::{MiniPngError, Result};
+use crate::pixel_type::PixelType;
+
+/// File formats images can be read from and written to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    /// Mini-PNG.
+    Mp,
+    /// Text art, see [`MiniPNG::from_text_art`](crate::MiniPNG::from_text_art).
+    Txt,
+    /// Netpbm black and white.
+    Pbm,
+    /// Netpbm gray levels.
+    Pgm,
+    /// Netpbm RGB.
+    Ppm,
+    /// Any of the Netpbm formats.
+    Pnm,
+    /// PNG.
+    Png,
+    /// Windows bitmap.
+    Bmp,
+}
+
+impl ImageFormat {
+    /// Guesses the format from the extension of `path`, ignoring its case.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "mp" => Ok(ImageFormat::Mp),
+            "txt" => Ok(ImageFormat::Txt),
+            "pbm" => Ok(ImageFormat::Pbm),
+            "pgm" => Ok(ImageFormat::Pgm),
+            "ppm" => Ok(ImageFormat::Ppm),
+            "pnm" => Ok(ImageFormat::Pnm),
+            "png" => Ok(ImageFormat::Png),
+            "bmp" => Ok(ImageFormat::Bmp),
+            _ => Err(MiniPngError::UnsupportedFormat(format!(
+                "extension '{}'",
+                extension
+            ))),
+        }
+    }
+
+    /// The only pixel type the format can store, if it is restricted to one.
+    pub fn pixel_type(self) -> Option<PixelType> {
+        match self {
+            ImageFormat::Pbm => Some(PixelType::BlackAndWhite),
+            ImageFormat::Pgm => Some(PixelType::GrayLevels),
+            ImageFormat::Ppm => Some(PixelType::RGB),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{ImageFormat, MiniPngError, PixelType};
+
+    #[test]
+    fn test_format_from_extension() {
+        let formats = [
+            ("image.mp", ImageFormat::Mp),
+            ("art.txt", ImageFormat::Txt),
+            ("a.pbm", ImageFormat::Pbm),
+            ("a.pgm", ImageFormat::Pgm),
+            ("a.ppm", ImageFormat::Ppm),
+            ("a.pnm", ImageFormat::Pnm),
+            ("dir.d/a.png", ImageFormat::Png),
+            ("a.bmp", ImageFormat::Bmp),
+            // the case is ignored
+            ("SHOUT.PNG", ImageFormat::Png),
+            ("a.Bmp", ImageFormat::Bmp),
+        ];
+        for (path, format) in formats {
+            assert_eq!(ImageFormat::from_path(Path::new(path)).unwrap(), format);
+        }
+    }
+
+    #[test]
+    fn test_format_unknown_extension() {
+        match ImageFormat::from_path(Path::new("photo.jpg")) {
+            Err(MiniPngError::UnsupportedFormat(message)) => {
+                assert_eq!(message, "extension 'jpg'")
+            }
+            other => panic!("Expected UnsupportedFormat, got {:?}", other),
+        }
+        assert!(matches!(
+            ImageFormat::from_path(Path::new("no-extension")),
+            Err(MiniPngError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_pixel_types() {
+        assert_eq!(
+            ImageFormat::Pbm.pixel_type(),
+            Some(PixelType::BlackAndWhite)
+        );
+        assert_eq!(ImageFormat::Pgm.pixel_type(), Some(PixelType::GrayLevels));
+        assert_eq!(ImageFormat::Ppm.pixel_type(), Some(PixelType::RGB));
+        for format in [
+            ImageFormat::Mp,
+            ImageFormat::Txt,
+            ImageFormat::Pnm,
+            ImageFormat::Png,
+        ] {
+            assert_eq!(format.pixel_type(), None);
+        }
+    }
+}
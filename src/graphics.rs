@@ -0,0 +1,155 @@
+use crate::base64;
+use crate::convert::ConvertOptions;
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::MiniPNG;
+use crate::pixel_type::PixelType;
+use crate::quantize::Quantizer;
+
+// largest base64 payload of a kitty escape sequence
+const KITTY_CHUNK_SIZE: usize = 4096;
+// sixel data characters start at `?`, for no pixel set
+const SIXEL_BASE: u8 = 63;
+
+/// Escape sequence protocols drawing real bitmaps in a terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphicsProtocol {
+    /// DEC sixels, supported by xterm, foot, WezTerm and others.
+    Sixel,
+    /// The kitty graphics protocol.
+    Kitty,
+    /// The inline images of iTerm2.
+    ITerm,
+}
+
+impl MiniPNG {
+    /// Draws the image with a terminal graphics protocol.
+    pub fn to_graphics(&self, protocol: GraphicsProtocol) -> Result<String> {
+        match protocol {
+            GraphicsProtocol::Sixel => self.to_sixel(),
+            GraphicsProtocol::Kitty => self.to_kitty(),
+            GraphicsProtocol::ITerm => self.to_iterm(),
+        }
+    }
+
+    /// Encodes the image as a sixel escape sequence.
+    ///
+    /// Palette images use their own palette as color registers. Other images
+    /// are converted to a palette of at most 256 colors first, which is
+    /// exact for black and white and gray level images.
+    pub fn to_sixel(&self) -> Result<String> {
+        let converted;
+        let image = match self.header.pixel_type {
+            PixelType::Palette => self,
+            _ => {
+                let options = ConvertOptions {
+                    quantizer: Quantizer::MedianCut,
+                    ..ConvertOptions::default()
+                };
+                converted = self.convert(PixelType::Palette, &options)?;
+                &converted
+            }
+        };
+        image.header.check_data_size(image.data.len())?;
+
+        let palette = image.palette.as_ref().ok_or(MiniPngError::MissingPalette)?;
+        let colors = &palette.colors[..palette.colors.len().min(256)];
+        if let Some(&index) = image.data.iter().find(|&&i| i as usize >= colors.len()) {
+            return Err(MiniPngError::InvalidPaletteIndex(index));
+        }
+
+        let (width, height) = (image.header.width, image.header.height);
+        // square pixels and the size of the image
+        let mut output = format!("\x1bPq\"1;1;{};{}", width, height);
+        for (i, color) in colors.iter().enumerate() {
+            // channels are given in percents
+            let [r, g, b] = color.map(|channel| (channel as u32 * 100 + 127) / 255);
+            output.push_str(&format!("#{};2;{};{};{}", i, r, g, b));
+        }
+
+        let rows: Vec<&[u8]> = image.data.chunks(width.max(1) as usize).collect();
+        for (band_index, band) in rows.chunks(6).enumerate() {
+            if band_index > 0 {
+                output.push('-');
+            }
+
+            let mut used: Vec<u8> = band.iter().flat_map(|row| row.iter().copied()).collect();
+            used.sort_unstable();
+            used.dedup();
+
+            for (i, &color) in used.iter().enumerate() {
+                if i > 0 {
+                    // back to the start of the band for the next color
+                    output.push('$');
+                }
+                output.push_str(&format!("#{}", color));
+
+                let sixels = (0..width as usize).map(|x| {
+                    let bits = band
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, row)| row[x] == color)
+                        .fold(0, |bits, (y, _)| bits | 1 << y);
+                    (SIXEL_BASE + bits) as char
+                });
+                push_runs(&mut output, sixels);
+            }
+        }
+
+        output.push_str("\x1b\\");
+        Ok(output)
+    }
+
+    /// Encodes the image as PNG data sent with the kitty graphics protocol,
+    /// split in escape sequences of at most 4096 base64 characters.
+    pub fn to_kitty(&self) -> Result<String> {
+        let payload = base64::encode(&self.to_png(Vec::new())?);
+        let chunks: Vec<&[u8]> = payload.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+
+        let mut output = String::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let more = (i + 1 < chunks.len()) as u8;
+            // the first chunk transmits and displays a PNG image
+            let keys = match i {
+                0 => format!("a=T,f=100,m={}", more),
+                _ => format!("m={}", more),
+            };
+            // base64 is ASCII
+            let chunk = String::from_utf8_lossy(chunk);
+            output.push_str(&format!("\x1b_G{};{}\x1b\\", keys, chunk));
+        }
+        Ok(output)
+    }
+
+    /// Encodes the image as PNG data shown inline with the iTerm2 protocol.
+    pub fn to_iterm(&self) -> Result<String> {
+        let png = self.to_png(Vec::new())?;
+        Ok(format!(
+            "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{}\x07",
+            png.len(),
+            base64::encode(&png)
+        ))
+    }
+}
+
+// writes sixel characters, repeating runs of more than 3 characters with `!`
+fn push_runs(output: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |output: &mut String, (c, count): (char, usize)| match count {
+        1..=3 => output.extend(std::iter::repeat_n(c, count)),
+        _ => output.push_str(&format!("!{}{}", count, c)),
+    };
+
+    for c in sixels {
+        run = match run {
+            Some((current, count)) if current == c => Some((c, count + 1)),
+            Some(previous) => {
+                flush(output, previous);
+                Some((c, 1))
+            }
+            None => Some((c, 1)),
+        };
+    }
+    if let Some(last) = run {
+        flush(output, last);
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests {
+    use crate::base64;
+    use crate::test_utils::sample;
+    use crate::{GraphicsProtocol, Header, MiniPNG, PixelType, RenderOptions};
+
+    // golden files checked with an independent sixel decoder and by decoding
+    // the base64 PNG payloads
+    #[test]
+    fn test_sixel_golden_files() {
+        for (bytes, expected) in [
+            (
+                include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+                include_str!("../minipng-samples/golden/A.sixel"),
+            ),
+            (
+                include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+                include_str!("../minipng-samples/golden/french-flag.sixel"),
+            ),
+            (
+                include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp"),
+                include_str!("../minipng-samples/golden/german-flag-palette.sixel"),
+            ),
+            (
+                include_bytes!("../minipng-samples/other/ok/gray.mp"),
+                include_str!("../minipng-samples/golden/gray.sixel"),
+            ),
+        ] {
+            assert_eq!(sample(bytes).to_sixel().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_kitty_and_iterm_golden_files() {
+        let image = sample(include_bytes!("../minipng-samples/other/ok/french-flag.mp"));
+        assert_eq!(
+            image.to_graphics(GraphicsProtocol::Kitty).unwrap(),
+            include_str!("../minipng-samples/golden/french-flag.kitty")
+        );
+        assert_eq!(
+            image.to_graphics(GraphicsProtocol::ITerm).unwrap(),
+            include_str!("../minipng-samples/golden/french-flag.iterm")
+        );
+    }
+
+    #[test]
+    fn test_sixel_bands_and_runs() {
+        // a column of 7 pixels spans two bands of 6 rows
+        let image = MiniPNG::bw_from_string("X\n \nX\nX\nX\nX\n \n").unwrap();
+        let sixel = image.to_sixel().unwrap();
+        let data = sixel.split_once("0;0;0").unwrap().1;
+        // white rows 0 and 2 to 5 in the first band, black rows 1 and 6
+        assert_eq!(data, "#0|$#1A-#1@\x1b\\");
+
+        let image = MiniPNG::bw_from_string("XXXX  X\n").unwrap();
+        let data = image.to_sixel().unwrap();
+        assert!(data.ends_with("#0!4@??@$#1!4?@@?\x1b\\"));
+    }
+
+    #[test]
+    fn test_kitty_chunks() {
+        // noise that doesn't compress, for a payload of several chunks
+        let header = Header {
+            width: 64,
+            height: 64,
+            pixel_type: PixelType::RGB,
+        };
+        let mut image = MiniPNG::new(header, None).unwrap();
+        let mut seed = 1u32;
+        for byte in image.data.iter_mut() {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            *byte = (seed >> 16) as u8;
+        }
+
+        let kitty = image.to_kitty().unwrap();
+        let sequences: Vec<&str> = kitty
+            .strip_suffix("\x1b\\")
+            .unwrap()
+            .split("\x1b\\")
+            .collect();
+        assert!(sequences.len() > 1);
+
+        let mut payload = String::new();
+        for (i, sequence) in sequences.iter().enumerate() {
+            let (keys, chunk) = sequence
+                .strip_prefix("\x1b_G")
+                .unwrap()
+                .split_once(';')
+                .unwrap();
+            let last = i + 1 == sequences.len();
+            let more = if last { "m=0" } else { "m=1" };
+            match i {
+                0 => assert_eq!(keys, format!("a=T,f=100,{}", more)),
+                _ => assert_eq!(keys, more),
+            }
+            assert!(chunk.len() <= 4096);
+            payload.push_str(chunk);
+        }
+        assert_eq!(payload, base64::encode(&image.to_png(Vec::new()).unwrap()));
+    }
+
+    #[test]
+    fn test_render_with_protocol() {
+        let image = sample(include_bytes!("../minipng-samples/other/ok/french-flag.mp"));
+        let options = RenderOptions {
+            protocol: Some(GraphicsProtocol::Sixel),
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            image.render(&options).unwrap(),
+            format!("\n{}", image.to_sixel().unwrap())
+        );
+        assert!(
+            image
+                .display_with(&options)
+                .unwrap()
+                .starts_with("Mini-PNG Image\n")
+        );
+    }
+}
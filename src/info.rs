@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use crate::borrowed::{MiniPngRef, RawBlocks};
+use crate::error::{MiniPngError, Result};
+use crate::json;
+use crate::mininpng::{Header, summary};
+use crate::toml;
+
+/// Metadata of a Mini-PNG file, read without decoding its pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInfo {
+    /// Content of the `H` block.
+    pub header: Header,
+    /// Total size of the pixel data in bytes.
+    pub data_size: usize,
+    /// Number of colors of the palette, if there is one.
+    pub palette_size: Option<usize>,
+    /// Text of every `C` block in file order.
+    pub comments: Vec<String>,
+    /// Every block in file order.
+    pub blocks: Vec<BlockInfo>,
+}
+
+/// Position and size of a block of a Mini-PNG file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockInfo {
+    /// Type byte of the block.
+    pub block_type: u8,
+    /// Offset of the type byte from the start of the file.
+    pub offset: usize,
+    /// Length of the content, without the type and length fields.
+    pub length: usize,
+}
+
+impl ImageInfo {
+    /// Parses a whole Mini-PNG file with the checks of [`MiniPngRef::parse`],
+    /// the data blocks are only measured.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let image = MiniPngRef::parse(bytes)?;
+        let blocks = RawBlocks::new(bytes)?
+            .map(|block| {
+                block.map(|block| BlockInfo {
+                    block_type: block.block_type,
+                    offset: block.offset,
+                    length: block.content.len(),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(ImageInfo {
+            header: image.header.clone(),
+            data_size: image.data_size(),
+            palette_size: image.palette.map(|_| image.palette_colors().count()),
+            comments: image.comments().map(str::to_string).collect(),
+            blocks,
+        })
+    }
+
+    /// Serializes the metadata as a JSON object, along with the `path` it
+    /// was read from.
+    pub fn to_json(&self, path: &str) -> String {
+        let palette_size = match self.palette_size {
+            Some(size) => size.to_string(),
+            None => "null".to_string(),
+        };
+        let blocks = json::array(self.blocks.iter().map(|block| {
+            format!(
+                "{{\"type\":{},\"offset\":{},\"length\":{}}}",
+                json::string(&(block.block_type as char).to_string()),
+                block.offset,
+                block.length
+            )
+        }));
+
+        format!(
+            "{{\"path\":{},\"width\":{},\"height\":{},\"pixel_type\":{},\"data_size\":{},\"palette_size\":{},\"comments\":{},\"blocks\":{}}}",
+            json::string(path),
+            self.header.width,
+            self.header.height,
+            self.header.pixel_type as u8,
+            self.data_size,
+            palette_size,
+            json::array(self.comments.iter().map(|comment| json::string(comment))),
+            blocks
+        )
+    }
+
+    /// Serializes the metadata as an `[[images]]` TOML table, along with the
+    /// `path` it was read from. The palette size is left out when there is
+    /// no palette.
+    pub fn to_toml(&self, path: &str) -> String {
+        let mut output = String::from("[[images]]\n");
+        output.push_str(&format!("path = {}\n", toml::string(path)));
+        output.push_str(&format!("width = {}\n", self.header.width));
+        output.push_str(&format!("height = {}\n", self.header.height));
+        output.push_str(&format!("pixel_type = {}\n", self.header.pixel_type as u8));
+        output.push_str(&format!("data_size = {}\n", self.data_size));
+        if let Some(size) = self.palette_size {
+            output.push_str(&format!("palette_size = {}\n", size));
+        }
+        output.push_str(&format!(
+            "comments = {}\n",
+            toml::array(self.comments.iter().map(|comment| toml::string(comment)))
+        ));
+
+        for block in &self.blocks {
+            output.push_str("\n[[images.blocks]]\n");
+            output.push_str(&format!(
+                "type = {}\n",
+                toml::string(&(block.block_type as char).to_string())
+            ));
+            output.push_str(&format!("offset = {}\n", block.offset));
+            output.push_str(&format!("length = {}\n", block.length));
+        }
+        output
+    }
+}
+
+impl Display for ImageInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            summary(
+                &self.header,
+                self.data_size,
+                self.palette_size,
+                &self.comments
+            )
+        )?;
+        writeln!(f, "Blocks:")?;
+        for block in &self.blocks {
+            writeln!(
+                f,
+                "  - '{}' at {:#x}: {} bytes",
+                block.block_type as char, block.offset, block.length
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&[u8]> for ImageInfo {
+    type Error = MiniPngError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        ImageInfo::parse(bytes)
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use crate::{BlockInfo, ImageInfo, MiniPNG, MiniPngError, PixelType};
+
+    const A: &[u8] = include_bytes!("../minipng-samples/bw/ok/A.mp");
+
+    #[test]
+    fn test_image_info_parse() {
+        let info = ImageInfo::parse(A).unwrap();
+
+        assert_eq!(info.header.width, 8);
+        assert_eq!(info.header.height, 10);
+        assert_eq!(info.header.pixel_type, PixelType::BlackAndWhite);
+        assert_eq!(info.data_size, 10);
+        assert_eq!(info.palette_size, None);
+        assert_eq!(info.comments, ["La lettre A"]);
+        assert_eq!(
+            info.blocks,
+            [
+                BlockInfo {
+                    block_type: b'H',
+                    offset: 8,
+                    length: 9
+                },
+                BlockInfo {
+                    block_type: b'C',
+                    offset: 22,
+                    length: 11
+                },
+                BlockInfo {
+                    block_type: b'D',
+                    offset: 38,
+                    length: 10
+                },
+            ]
+        );
+
+        let palette = include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp");
+        assert_eq!(
+            ImageInfo::try_from(palette.as_slice())
+                .unwrap()
+                .palette_size,
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_image_info_rejects_invalid_files() {
+        assert!(matches!(
+            ImageInfo::parse(b"Mini-PNX"),
+            Err(MiniPngError::InvalidMagicNumber)
+        ));
+        assert!(ImageInfo::parse(&A[..A.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_image_info_text_matches_display() {
+        let text = ImageInfo::parse(A).unwrap().to_string();
+        let display = MiniPNG::read_from(A).unwrap().display().unwrap();
+
+        let (summary, blocks) = text.split_once("Blocks:\n").unwrap();
+        assert!(display.starts_with(summary));
+        assert_eq!(
+            blocks,
+            "  - 'H' at 0x8: 9 bytes\n  - 'C' at 0x16: 11 bytes\n  - 'D' at 0x26: 10 bytes\n"
+        );
+    }
+
+    #[test]
+    fn test_image_info_json() {
+        assert_eq!(
+            ImageInfo::parse(A).unwrap().to_json("A.mp"),
+            concat!(
+                r#"{"path":"A.mp","width":8,"height":10,"pixel_type":0,"data_size":10,"#,
+                r#""palette_size":null,"comments":["La lettre A"],"blocks":["#,
+                r#"{"type":"H","offset":8,"length":9},{"type":"C","offset":22,"length":11},"#,
+                r#"{"type":"D","offset":38,"length":10}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_image_info_toml() {
+        let mut info = ImageInfo::parse(A).unwrap();
+        info.comments.push("tab\tand \"quotes\"\u{7f}".to_string());
+        info.blocks.truncate(1);
+
+        assert_eq!(
+            info.to_toml("A.mp"),
+            r#"[[images]]
+path = "A.mp"
+width = 8
+height = 10
+pixel_type = 0
+data_size = 10
+comments = ["La lettre A", "tab\tand \"quotes\"\u007f"]
+
+[[images.blocks]]
+type = "H"
+offset = 8
+length = 9
+"#
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::borrowed::RawBlock;
+use crate::mininpng::Header;
+
+// bytes per line of a hex dump
+const HEX_LINE: usize = 16;
+// longest comment shown in a preview, in characters
+const PREVIEW_LENGTH: usize = 40;
+
+impl RawBlock<'_> {
+    /// Short description of the content: the header fields, the number of
+    /// palette colors, the comment text or the size of the pixel data.
+    pub fn preview(&self) -> String {
+        match self.block_type {
+            b'H' => match Header::parse(self.content) {
+                Ok(header) => format!(
+                    "{}x{}, pixel type {}",
+                    header.width, header.height, header.pixel_type
+                ),
+                Err(e) => format!("invalid header: {}", e),
+            },
+            b'P' => match self.content.len() % 3 {
+                0 => format!("{} colors", self.content.len() / 3),
+                extra => format!(
+                    "{} colors and {} trailing bytes",
+                    self.content.len() / 3,
+                    extra
+                ),
+            },
+            b'C' => {
+                let text = String::from_utf8_lossy(self.content);
+                let mut preview: String = text.chars().take(PREVIEW_LENGTH).collect();
+                if text.chars().count() > PREVIEW_LENGTH {
+                    preview.push('…');
+                }
+                format!("{:?}", preview)
+            }
+            b'D' => format!("{} bytes of pixel data", self.content.len()),
+            _ => "unknown block type".to_string(),
+        }
+    }
+
+    /// Dumps the whole block like a hex editor, one line for the type, one
+    /// for the length and then 16 bytes of content per line with their
+    /// offsets in the file and their ASCII characters.
+    pub fn hex_dump(&self) -> String {
+        let mut output = format!(
+            "  {:08x}  {:<48} type '{}'\n",
+            self.offset,
+            format!("{:02x}", self.block_type),
+            (self.block_type as char).escape_default()
+        );
+        let length = (self.content.len() as u32).to_be_bytes();
+        output.push_str(&format!(
+            "  {:08x}  {:<48} length {}\n",
+            self.offset + 1,
+            hex(&length),
+            self.content.len()
+        ));
+
+        let start = self.offset + 5;
+        for (i, line) in self.content.chunks(HEX_LINE).enumerate() {
+            let ascii: String = line
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7e => byte as char,
+                    _ => '.',
+                })
+                .collect();
+            output.push_str(&format!(
+                "  {:08x}  {:<48} |{}|\n",
+                start + i * HEX_LINE,
+                hex(line),
+                ascii
+            ));
+        }
+        output
+    }
+}
+
+// bytes as space separated hex pairs
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
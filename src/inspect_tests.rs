@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use crate::{RawBlock, RawBlocks};
+
+    fn block(block_type: u8, content: &[u8]) -> RawBlock<'_> {
+        RawBlock {
+            index: 0,
+            block_type,
+            offset: 8,
+            content,
+        }
+    }
+
+    #[test]
+    fn test_block_previews() {
+        let bytes = include_bytes!("../minipng-samples/bw/ok/A.mp");
+        let previews: Vec<String> = RawBlocks::new(bytes)
+            .unwrap()
+            .map(|block| block.unwrap().preview())
+            .collect();
+        assert_eq!(
+            previews,
+            [
+                "8x10, pixel type 0 (1 bit black and white)",
+                "\"La lettre A\"",
+                "10 bytes of pixel data",
+            ]
+        );
+
+        assert_eq!(
+            block(b'P', &[0; 7]).preview(),
+            "2 colors and 1 trailing bytes"
+        );
+        assert_eq!(block(b'P', &[0; 9]).preview(), "3 colors");
+        assert_eq!(
+            block(b'H', &[0; 4]).preview(),
+            "invalid header: Header block too small"
+        );
+        assert_eq!(block(b'X', &[]).preview(), "unknown block type");
+    }
+
+    #[test]
+    fn test_comment_previews_are_shortened() {
+        let comment = "a".repeat(50);
+        let preview = block(b'C', comment.as_bytes()).preview();
+        assert_eq!(preview, format!("\"{}…\"", "a".repeat(40)));
+
+        // quotes and control characters are escaped
+        assert_eq!(block(b'C', b"say \"hi\"\n").preview(), r#""say \"hi\"\n""#);
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let content: Vec<u8> = (b'a'..=b'q').chain([0, 0xff]).collect();
+        let dump = block(b'C', &content).hex_dump();
+        let lines: Vec<&str> = dump.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["00000008", "43", "type", "'C'"]
+        );
+        assert_eq!(
+            lines[1].split_whitespace().collect::<Vec<_>>(),
+            ["00000009", "00", "00", "00", "13", "length", "19"]
+        );
+        assert_eq!(
+            lines[2],
+            "  0000000d  61 62 63 64 65 66 67 68 69 6a 6b 6c 6d 6e 6f 70  |abcdefghijklmnop|"
+        );
+        // the ASCII column stays aligned on the last line
+        assert_eq!(lines[3], format!("  0000001d  {:<48} |q..|", "71 00 ff"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Parser, writer and renderer for the Mini-PNG image format.
+//!
+//! A Mini-PNG file starts with the magic `Mini-PNG` followed by a sequence of
+//! blocks, each made of a one byte type, a big-endian `u32` length and the
+//! block content:
+//! - `H`: the mandatory header (width, height and pixel type)
+//! - `P`: the palette, mandatory for palette images
+//! - `C`: a comment
+//! - `D`: pixel data, concatenated in file order
+//!
+//! ```no_run
+//! use minipng::MiniPNG;
+//!
+//! let bytes = std::fs::read("image.mp").unwrap();
+//! let image: MiniPNG = bytes.try_into().unwrap();
+//! println!("{}x{}", image.header.width, image.header.height);
+//! ```
+
+#![warn(missing_docs)]
+
+mod base64;
+mod base64_tests;
+mod bmp;
+mod bmp_tests;
+mod borrowed;
+mod borrowed_tests;
+mod convert;
+mod convert_tests;
+mod dither;
+mod dither_tests;
+mod error;
+mod format;
+mod format_tests;
+mod graphics;
+mod graphics_tests;
+mod info;
+mod info_tests;
+mod inspect;
+mod inspect_tests;
+mod json;
+mod limits;
+mod limits_tests;
+mod mininpng;
+mod mininpng_tests;
+mod options;
+mod pixel;
+mod pixel_tests;
+mod pixel_type;
+mod png;
+mod png_tests;
+mod pnm;
+mod pnm_tests;
+mod quantize;
+mod quantize_tests;
+mod reader;
+mod reader_tests;
+mod render;
+mod render_tests;
+mod scale;
+mod scale_tests;
+mod terminal;
+mod terminal_tests;
+#[cfg(test)]
+mod test_utils;
+mod text_art;
+mod text_art_tests;
+mod toml;
+mod validate;
+mod validate_tests;
+mod writer;
+mod writer_tests;
+mod zlib;
+mod zlib_tests;
+
+pub use borrowed::{MiniPngRef, RawBlock, RawBlocks};
+pub use convert::{ConvertOptions, luminance};
+pub use dither::Dither;
+pub use error::{MiniPngError, Result};
+pub use format::ImageFormat;
+pub use graphics::GraphicsProtocol;
+pub use info::{BlockInfo, ImageInfo};
+pub use limits::DecodeLimits;
+pub use mininpng::{BwChars, Header, MiniPNG, Palette};
+pub use options::{DecodeOptions, Strictness, Warning};
+pub use pixel::Pixel;
+pub use pixel_type::PixelType;
+pub use quantize::{Quantizer, median_cut, octree};
+pub use reader::{Block, MiniPngReader};
+pub use render::{RenderMode, RenderOptions};
+pub use scale::BwScaling;
+pub use terminal::ColorSupport;
+pub use text_art::DEFAULT_RAMP;
+pub use validate::{Finding, Report, validate};
+pub use writer::{DEFAULT_MAX_BLOCK_SIZE, MiniPngWriter};
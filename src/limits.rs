@@ -0,0 +1,131 @@
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::Header;
+
+/// Upper bounds enforced while decoding untrusted files.
+///
+/// Exceeding any of them returns [`MiniPngError::LimitExceeded`] before the
+/// offending block content is read, so a forged header or block length
+/// cannot make the decoder allocate an arbitrary amount of memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeLimits {
+    /// Maximum width of the image, in pixels.
+    pub max_width: u32,
+    /// Maximum height of the image, in pixels.
+    pub max_height: u32,
+    /// Maximum value of `width * height`.
+    pub max_pixels: u64,
+    /// Maximum sum of the declared lengths of every block.
+    pub max_total_block_bytes: u64,
+    /// Maximum number of `C` blocks.
+    pub max_comment_count: usize,
+    /// Maximum length of a single comment, in bytes.
+    pub max_comment_length: u32,
+    /// Maximum number of zero bytes added to short pixel data in lenient
+    /// mode, which also count towards `max_total_block_bytes`.
+    pub max_padding_bytes: u64,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_width: 1 << 16,
+            max_height: 1 << 16,
+            max_pixels: 1 << 28,
+            max_total_block_bytes: 1 << 30,
+            max_comment_count: 1024,
+            max_comment_length: 1 << 16,
+            max_padding_bytes: 1 << 24,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// Limits that accept anything the format can express.
+    pub fn unlimited() -> Self {
+        DecodeLimits {
+            max_width: u32::MAX,
+            max_height: u32::MAX,
+            max_pixels: u64::MAX,
+            max_total_block_bytes: u64::MAX,
+            max_comment_count: usize::MAX,
+            max_comment_length: u32::MAX,
+            max_padding_bytes: u64::MAX,
+        }
+    }
+
+    pub(crate) fn check_header(&self, header: &Header) -> Result<()> {
+        check("width", header.width as u64, self.max_width as u64)?;
+        check("height", header.height as u64, self.max_height as u64)?;
+        check(
+            "pixel count",
+            header.width as u64 * header.height as u64,
+            self.max_pixels,
+        )
+    }
+}
+
+// running totals of the blocks read so far, checked against the limits
+// before the content of each block is read
+#[derive(Debug, Clone)]
+pub(crate) struct BlockBudget {
+    pub(crate) limits: DecodeLimits,
+    total_block_bytes: u64,
+    comment_count: usize,
+}
+
+impl BlockBudget {
+    pub(crate) fn new(limits: DecodeLimits) -> Self {
+        BlockBudget {
+            limits,
+            total_block_bytes: 0,
+            comment_count: 0,
+        }
+    }
+
+    pub(crate) fn spend(&mut self, block_type: u8, length: u32) -> Result<()> {
+        self.total_block_bytes = self.total_block_bytes.saturating_add(length as u64);
+        check(
+            "total block bytes",
+            self.total_block_bytes,
+            self.limits.max_total_block_bytes,
+        )?;
+
+        if block_type == b'C' {
+            self.comment_count += 1;
+            check(
+                "comment count",
+                self.comment_count as u64,
+                self.limits.max_comment_count as u64,
+            )?;
+            check(
+                "comment length",
+                length as u64,
+                self.limits.max_comment_length as u64,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // zero bytes appended to short pixel data, checked before allocating them
+    pub(crate) fn pad(&mut self, missing: usize) -> Result<()> {
+        check(
+            "padding bytes",
+            missing as u64,
+            self.limits.max_padding_bytes,
+        )?;
+        self.total_block_bytes = self.total_block_bytes.saturating_add(missing as u64);
+        check(
+            "total block bytes",
+            self.total_block_bytes,
+            self.limits.max_total_block_bytes,
+        )
+    }
+}
+
+fn check(limit: &'static str, value: u64, max: u64) -> Result<()> {
+    if value > max {
+        return Err(MiniPngError::LimitExceeded { limit, value, max });
+    }
+    Ok(())
+}
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::file;
+    use crate::{
+        DecodeLimits, DecodeOptions, Header, MiniPNG, MiniPngError, MiniPngReader, MiniPngRef,
+        PixelType,
+    };
+
+    fn header_content(width: u32, height: u32, pixel_type: u8) -> Vec<u8> {
+        let mut content = width.to_be_bytes().to_vec();
+        content.extend_from_slice(&height.to_be_bytes());
+        content.push(pixel_type);
+        content
+    }
+
+    #[test]
+    fn test_limits_reject_huge_header() {
+        let bytes = file(&[(b'H', &header_content(100_000, 1, 1)), (b'D', &[0])]);
+
+        match MiniPngRef::parse(&bytes).unwrap_err().root_cause() {
+            MiniPngError::LimitExceeded { limit, value, max } => {
+                assert_eq!(*limit, "width");
+                assert_eq!(*value, 100_000);
+                assert_eq!(*max, 1 << 16);
+            }
+            other => panic!("Expected LimitExceeded, got {:?}", other),
+        }
+
+        let limits = DecodeLimits {
+            max_pixels: 15,
+            ..DecodeLimits::default()
+        };
+        let bytes = file(&[(b'H', &header_content(4, 4, 1)), (b'D', &[0; 16])]);
+        let error = MiniPngReader::with_limits(bytes.as_slice(), limits)
+            .and_then(|r| r.read_image())
+            .unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "pixel count",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_limits_checked_before_reading_content() {
+        // declares a 2 GiB data block but only holds a few bytes
+        let mut bytes = file(&[(b'H', &header_content(1, 1, 1))]);
+        bytes.push(b'D');
+        bytes.extend_from_slice(&0x8000_0000u32.to_be_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+
+        let error = MiniPNG::read_from(bytes.as_slice()).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "total block bytes",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_limits_on_comments() {
+        let limits = DecodeLimits {
+            max_comment_count: 1,
+            max_comment_length: 4,
+            ..DecodeLimits::default()
+        };
+
+        let long = file(&[(b'C', b"too long")]);
+        let error = MiniPngRef::parse_with_limits(&long, limits.clone()).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "comment length",
+                value: 8,
+                max: 4
+            }
+        ));
+
+        let many = file(&[(b'C', b"a"), (b'C', b"b")]);
+        let error = MiniPngRef::parse_with_limits(&many, limits).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "comment count",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_size_arithmetic_is_checked() {
+        let header = Header {
+            width: u32::MAX,
+            height: u32::MAX,
+            pixel_type: PixelType::RGB,
+        };
+        assert!(matches!(
+            header.data_size(),
+            Err(MiniPngError::DimensionsOverflow { .. })
+        ));
+
+        let bytes = file(&[(b'H', &header_content(u32::MAX, u32::MAX, 3)), (b'D', &[0])]);
+        assert!(matches!(
+            MiniPngReader::with_limits(bytes.as_slice(), DecodeLimits::unlimited())
+                .and_then(|r| r.read_image()),
+            Err(MiniPngError::DimensionsOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_limits_on_lenient_padding() {
+        // a forged header declaring 768 MiB of pixels followed by a single byte
+        let bytes = file(&[(b'H', &header_content(16384, 16384, 3)), (b'D', &[0])]);
+        let error =
+            MiniPNG::read_with_options(bytes.as_slice(), DecodeOptions::lenient()).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "padding bytes",
+                value: 805_306_367,
+                ..
+            }
+        ));
+
+        // the padding also counts towards the block bytes
+        let options = DecodeOptions {
+            limits: DecodeLimits {
+                max_total_block_bytes: 20,
+                ..DecodeLimits::default()
+            },
+            ..DecodeOptions::lenient()
+        };
+        let bytes = file(&[(b'H', &header_content(4, 4, 1)), (b'D', &[0])]);
+        let error = MiniPNG::read_with_options(bytes.as_slice(), options.clone()).unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::LimitExceeded {
+                limit: "total block bytes",
+                value: 25,
+                max: 20
+            }
+        ));
+        let bytes = file(&[(b'H', &header_content(2, 2, 1)), (b'D', &[0])]);
+        assert!(MiniPNG::read_with_options(bytes.as_slice(), options).is_ok());
+    }
+}
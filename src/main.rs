@@ -1,14 +1,18 @@
-use std::{fs, path::PathBuf, process};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+};
 
-use clap::Parser;
+use clap::{Args as ClapArgs, Parser, ValueEnum};
 
-use crate::error::{MiniPngError, Result};
-use crate::mininpng::MiniPNG;
-
-mod error;
-mod mininpng;
-mod mininpng_tests;
-mod pixel_type;
+use minipng::{
+    BwChars, BwScaling, ColorSupport, ConvertOptions, DecodeOptions, Dither, GraphicsProtocol,
+    ImageFormat, ImageInfo, MiniPNG, MiniPngError, PixelType, Quantizer, RawBlocks, RenderMode,
+    RenderOptions, Report, Result,
+};
 
 /// CLI for the mini png exercices
 #[derive(Parser, Debug)]
@@ -23,7 +27,40 @@ enum Commands {
     Display {
         /// paths of the Mini-PNG images
         paths: Vec<PathBuf>,
+
+        /// recover from malformed files instead of rejecting them
+        #[arg(long)]
+        lenient: bool,
+
+        /// how the pixels are drawn
+        #[arg(long, value_enum, default_value_t = ModeArg::Full)]
+        mode: ModeArg,
+
+        /// colors used to draw the pixels, `auto` checks NO_COLOR, COLORTERM and TERM
+        #[arg(long, value_enum, default_value_t = ColorArg::Auto)]
+        color: ColorArg,
+
+        /// maximum number of columns, the width of the terminal by default
+        #[arg(long)]
+        width: Option<u32>,
+
+        /// maximum number of lines, the height of the terminal by default
+        #[arg(long)]
+        height: Option<u32>,
+
+        /// draw every pixel even if the image is larger than the terminal
+        #[arg(long, conflicts_with_all = ["width", "height"])]
+        no_scale: bool,
+
+        /// how black and white pixels are merged when the image is shrunk
+        #[arg(long, value_enum, default_value_t = BwScalingArg::Majority)]
+        bw_scaling: BwScalingArg,
+
+        /// draw a bitmap with a terminal graphics protocol instead of characters
+        #[arg(long, value_enum, conflicts_with_all = ["mode", "color", "width", "height", "no_scale", "bw_scaling"])]
+        protocol: Option<ProtocolArg>,
     },
+    /// turn text art into a Mini-PNG image
     Encode {
         /// path of the input text file
         input: PathBuf,
@@ -31,32 +68,598 @@ enum Commands {
         /// path of the output Mini-PNG file
         #[arg(short, long)]
         output: PathBuf,
+
+        #[command(flatten)]
+        chars: TextChars,
+    },
+    /// turn a Mini-PNG image back into text art
+    Decode {
+        /// path of the input Mini-PNG file
+        input: PathBuf,
+
+        /// path of the output text file, the text is printed if omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        chars: TextChars,
+    },
+    /// print the metadata of Mini-PNG files without their pixels
+    Info {
+        /// paths of the Mini-PNG images
+        paths: Vec<PathBuf>,
+
+        /// output format
+        #[arg(long, value_enum, default_value_t = InfoFormat::Text)]
+        format: InfoFormat,
+    },
+    /// list the blocks of a Mini-PNG file in file order
+    Blocks {
+        /// path of the Mini-PNG file
+        path: PathBuf,
+
+        /// dump the content of every block in hexadecimal
+        #[arg(long)]
+        hex: bool,
+    },
+    /// check Mini-PNG files and report non-fatal findings
+    Validate {
+        /// paths of the Mini-PNG images
+        paths: Vec<PathBuf>,
+
+        /// print the reports as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
+    /// convert an image between formats and pixel types
+    Convert {
+        /// path of the input image
+        input: PathBuf,
+
+        /// path of the output image
+        output: PathBuf,
+
+        /// format of the input, guessed from its extension by default
+        #[arg(long, value_enum)]
+        from: Option<FormatArg>,
+
+        /// format of the output, guessed from its extension by default
+        #[arg(long, value_enum)]
+        to: Option<FormatArg>,
+
+        /// pixel type of the output, defaults to the one of the input or the
+        /// only one the output format can store
+        #[arg(long, value_enum)]
+        pixel_type: Option<PixelTypeArg>,
+
+        /// dithering used when reducing to black and white or to a palette
+        #[arg(long, value_enum, default_value_t = DitherArg::None)]
+        dither: DitherArg,
+
+        /// maximum number of colors of a palette output
+        #[arg(long, default_value_t = 256)]
+        colors: usize,
+
+        /// how the palette is built when the image has too many colors
+        #[arg(long, value_enum, default_value_t = QuantizerArg::MedianCut)]
+        quantizer: QuantizerArg,
+
+        /// gray level from which a pixel becomes white
+        #[arg(long, default_value_t = 128)]
+        threshold: u8,
+
+        /// write Netpbm outputs in the plain ASCII variant
+        #[arg(long)]
+        ascii: bool,
     },
 }
 
+#[derive(ClapArgs, Debug)]
+struct TextChars {
+    /// character of the white pixels
+    #[arg(long, default_value_t = 'X')]
+    white: char,
+
+    /// character of the black pixels, a space by default
+    #[arg(long, default_value_t = ' ', hide_default_value = true)]
+    black: char,
+}
+
+impl From<TextChars> for BwChars {
+    fn from(chars: TextChars) -> Self {
+        BwChars {
+            white: chars.white,
+            black: chars.black,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum InfoFormat {
+    Text,
+    Json,
+    Toml,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FormatArg {
+    // Mini-PNG
+    Mp,
+    // text art, as read by `encode`
+    Txt,
+    Pbm,
+    Pgm,
+    Ppm,
+    // any of the Netpbm formats
+    Pnm,
+    Png,
+    Bmp,
+}
+
+impl From<FormatArg> for ImageFormat {
+    fn from(arg: FormatArg) -> Self {
+        match arg {
+            FormatArg::Mp => ImageFormat::Mp,
+            FormatArg::Txt => ImageFormat::Txt,
+            FormatArg::Pbm => ImageFormat::Pbm,
+            FormatArg::Pgm => ImageFormat::Pgm,
+            FormatArg::Ppm => ImageFormat::Ppm,
+            FormatArg::Pnm => ImageFormat::Pnm,
+            FormatArg::Png => ImageFormat::Png,
+            FormatArg::Bmp => ImageFormat::Bmp,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum PixelTypeArg {
+    Bw,
+    Gray,
+    Palette,
+    Rgb,
+}
+
+impl From<PixelTypeArg> for PixelType {
+    fn from(arg: PixelTypeArg) -> Self {
+        match arg {
+            PixelTypeArg::Bw => PixelType::BlackAndWhite,
+            PixelTypeArg::Gray => PixelType::GrayLevels,
+            PixelTypeArg::Palette => PixelType::Palette,
+            PixelTypeArg::Rgb => PixelType::RGB,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ModeArg {
+    Full,
+    HalfBlock,
+    Quadrant,
+    Braille,
+}
+
+impl From<ModeArg> for RenderMode {
+    fn from(arg: ModeArg) -> Self {
+        match arg {
+            ModeArg::Full => RenderMode::Full,
+            ModeArg::HalfBlock => RenderMode::HalfBlock,
+            ModeArg::Quadrant => RenderMode::Quadrant,
+            ModeArg::Braille => RenderMode::Braille,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ColorArg {
+    Auto,
+    Always,
+    Never,
+    #[value(name = "256")]
+    Ansi256,
+    #[value(name = "16")]
+    Ansi16,
+}
+
+impl From<ColorArg> for ColorSupport {
+    fn from(arg: ColorArg) -> Self {
+        match arg {
+            ColorArg::Auto => ColorSupport::detect(),
+            ColorArg::Always => ColorSupport::TrueColor,
+            ColorArg::Never => ColorSupport::Monochrome,
+            ColorArg::Ansi256 => ColorSupport::Ansi256,
+            ColorArg::Ansi16 => ColorSupport::Ansi16,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum BwScalingArg {
+    Majority,
+    Coverage,
+}
+
+impl From<BwScalingArg> for BwScaling {
+    fn from(arg: BwScalingArg) -> Self {
+        match arg {
+            BwScalingArg::Majority => BwScaling::Majority,
+            BwScalingArg::Coverage => BwScaling::Coverage,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ProtocolArg {
+    Sixel,
+    Kitty,
+    Iterm,
+}
+
+impl From<ProtocolArg> for GraphicsProtocol {
+    fn from(arg: ProtocolArg) -> Self {
+        match arg {
+            ProtocolArg::Sixel => GraphicsProtocol::Sixel,
+            ProtocolArg::Kitty => GraphicsProtocol::Kitty,
+            ProtocolArg::Iterm => GraphicsProtocol::ITerm,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum DitherArg {
+    None,
+    FloydSteinberg,
+    Atkinson,
+    Bayer2,
+    Bayer4,
+    Bayer8,
+}
+
+impl From<DitherArg> for Dither {
+    fn from(arg: DitherArg) -> Self {
+        match arg {
+            DitherArg::None => Dither::None,
+            DitherArg::FloydSteinberg => Dither::FloydSteinberg,
+            DitherArg::Atkinson => Dither::Atkinson,
+            DitherArg::Bayer2 => Dither::Bayer2,
+            DitherArg::Bayer4 => Dither::Bayer4,
+            DitherArg::Bayer8 => Dither::Bayer8,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum QuantizerArg {
+    Exact,
+    MedianCut,
+    Octree,
+}
+
+impl From<QuantizerArg> for Quantizer {
+    fn from(arg: QuantizerArg) -> Self {
+        match arg {
+            QuantizerArg::Exact => Quantizer::Exact,
+            QuantizerArg::MedianCut => Quantizer::MedianCut,
+            QuantizerArg::Octree => Quantizer::Octree,
+        }
+    }
+}
+
 fn run() -> Result<()> {
     let args = Args::parse();
 
     match args.command {
-        Commands::Display { paths } => {
+        Commands::Display {
+            paths,
+            lenient,
+            mode,
+            color,
+            width,
+            height,
+            no_scale,
+            bw_scaling,
+            protocol,
+        } => {
+            let options = match lenient {
+                true => DecodeOptions::lenient(),
+                false => DecodeOptions::default(),
+            };
+            // the terminal size only matters when printing to it
+            let terminal = match no_scale || !io::stdout().is_terminal() {
+                true => None,
+                false => terminal_size(),
+            };
+            let fit = match (width, height, terminal) {
+                (None, None, None) => None,
+                (width, height, terminal) => Some((
+                    width
+                        .or(terminal.map(|(columns, _)| columns))
+                        .unwrap_or(u32::MAX),
+                    height
+                        .or(terminal.map(|(_, lines)| lines))
+                        .unwrap_or(u32::MAX),
+                )),
+            };
+            let render_options = RenderOptions {
+                mode: mode.into(),
+                color: color.into(),
+                fit,
+                bw_scaling: bw_scaling.into(),
+                protocol: protocol.map(GraphicsProtocol::from),
+            };
+
             for path in paths {
                 let bytes = fs::read(&path).map_err(|e| MiniPngError::FileRead {
                     path: path.display().to_string(),
                     source: e,
                 })?;
-                let image: MiniPNG = bytes.try_into()?;
-                let output = image.display()?;
+                let (image, warnings) =
+                    MiniPNG::read_with_options(bytes.as_slice(), options.clone())?;
+                for warning in warnings {
+                    eprintln!("Warning: {}: {}", path.display(), warning);
+                }
+                let output = image.display_with(&render_options)?;
                 println!("{}", output);
             }
         }
-        Commands::Encode { input, output } => {
+        Commands::Encode {
+            input,
+            output,
+            chars,
+        } => {
             let text = fs::read_to_string(&input).map_err(|e| MiniPngError::FileRead {
                 path: input.display().to_string(),
                 source: e,
             })?;
-            let image = MiniPNG::bw_from_string(&text)?;
+            let image = MiniPNG::from_text_art(&text, chars.into())?;
             image.save(output)?;
         }
+        Commands::Decode {
+            input,
+            output,
+            chars,
+        } => {
+            let text = read_image(&input, ImageFormat::Mp)?.to_text_art(chars.into())?;
+            match output {
+                Some(path) => fs::write(&path, text).map_err(|e| MiniPngError::FileWrite {
+                    path: path.display().to_string(),
+                    source: e,
+                })?,
+                None => print!("{}", text),
+            }
+        }
+        Commands::Info { paths, format } => info(&paths, format)?,
+        Commands::Blocks { path, hex } => blocks(&path, hex)?,
+        Commands::Validate { paths, json } => validate(&paths, json)?,
+        Commands::Convert {
+            input,
+            output,
+            from,
+            to,
+            pixel_type,
+            dither,
+            colors,
+            quantizer,
+            threshold,
+            ascii,
+        } => {
+            let from = from
+                .map(ImageFormat::from)
+                .map_or_else(|| ImageFormat::from_path(&input), Ok)?;
+            let to = to
+                .map(ImageFormat::from)
+                .map_or_else(|| ImageFormat::from_path(&output), Ok)?;
+
+            let image = read_image(&input, from)?;
+            let target = pixel_type
+                .map(PixelType::from)
+                .or(to.pixel_type())
+                .unwrap_or(image.header.pixel_type);
+            if let Some(expected) = to.pixel_type()
+                && expected != target
+            {
+                return Err(MiniPngError::UnsupportedFormat(format!(
+                    "{:?} output can't store {:?} images",
+                    to, target
+                )));
+            }
+
+            let options = ConvertOptions {
+                threshold,
+                quantizer: quantizer.into(),
+                max_colors: colors,
+                dither: dither.into(),
+            };
+            let image = image.convert(target, &options)?;
+            write_image(&image, &output, to, ascii)?;
+        }
+    }
+
+    Ok(())
+}
+
+// columns and lines of the terminal, from `COLUMNS` and `LINES` or from
+// `stty size` when they are not both set
+fn terminal_size() -> Option<(u32, u32)> {
+    let var = |name| env::var(name).ok()?.parse::<u32>().ok();
+    if let (Some(columns), Some(lines)) = (var("COLUMNS"), var("LINES")) {
+        return Some((columns, lines));
+    }
+
+    // stty reads the terminal from its standard input
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let text = String::from_utf8(output.stdout).ok()?;
+    match text.split_whitespace().collect::<Vec<_>>()[..] {
+        [lines, columns] => Some((columns.parse().ok()?, lines.parse().ok()?)),
+        _ => None,
+    }
+    .filter(|&(columns, lines)| columns > 0 && lines > 0)
+}
+
+fn read_image(path: &Path, format: ImageFormat) -> Result<MiniPNG> {
+    let bytes = fs::read(path).map_err(|e| MiniPngError::FileRead {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    match format {
+        ImageFormat::Mp => MiniPNG::try_from(bytes),
+        ImageFormat::Txt => {
+            let text = String::from_utf8(bytes)
+                .map_err(|_| MiniPngError::UnsupportedFormat("text that is not UTF-8".into()))?;
+            MiniPNG::from_text_art(&text, BwChars::default())
+        }
+        ImageFormat::Pbm | ImageFormat::Pgm | ImageFormat::Ppm | ImageFormat::Pnm => {
+            MiniPNG::from_pnm(bytes.as_slice())
+        }
+        ImageFormat::Png => MiniPNG::from_png(bytes.as_slice()),
+        ImageFormat::Bmp => MiniPNG::from_bmp(bytes.as_slice()),
+    }
+}
+
+fn write_image(image: &MiniPNG, path: &Path, format: ImageFormat, ascii: bool) -> Result<()> {
+    let file = File::create(path).map_err(|e| MiniPngError::FileWrite {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+    let mut writer = BufWriter::new(file);
+
+    let result = match format {
+        ImageFormat::Mp => image.write_to(&mut writer).map(|_| ()),
+        ImageFormat::Txt => image
+            .to_text_art(BwChars::default())
+            .and_then(|text| writer.write_all(text.as_bytes()).map_err(MiniPngError::Io)),
+        ImageFormat::Pbm | ImageFormat::Pgm | ImageFormat::Ppm | ImageFormat::Pnm => {
+            image.to_pnm(&mut writer, ascii).map(|_| ())
+        }
+        ImageFormat::Png => image.to_png(&mut writer).map(|_| ()),
+        ImageFormat::Bmp => image.to_bmp(&mut writer).map(|_| ()),
+    }
+    .and_then(|_| writer.flush().map_err(MiniPngError::Io));
+    result.map_err(|e| match e {
+        MiniPngError::Io(source) => MiniPngError::FileWrite {
+            path: path.display().to_string(),
+            source,
+        },
+        e => e,
+    })
+}
+
+fn info(paths: &[PathBuf], format: InfoFormat) -> Result<()> {
+    let mut infos = Vec::new();
+    for path in paths {
+        let bytes = fs::read(path).map_err(|e| MiniPngError::FileRead {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        infos.push((path.display().to_string(), ImageInfo::parse(&bytes)?));
+    }
+
+    match format {
+        InfoFormat::Text => {
+            for (i, (path, info)) in infos.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{}:", path);
+                print!("{}", info);
+            }
+        }
+        InfoFormat::Json => {
+            let objects: Vec<String> = infos
+                .iter()
+                .map(|(path, info)| info.to_json(path))
+                .collect();
+            println!("[{}]", objects.join(","));
+        }
+        InfoFormat::Toml => {
+            let tables: Vec<String> = infos
+                .iter()
+                .map(|(path, info)| info.to_toml(path))
+                .collect();
+            print!("{}", tables.join("\n"));
+        }
+    }
+
+    Ok(())
+}
+
+fn blocks(path: &Path, hex: bool) -> Result<()> {
+    let bytes = fs::read(path).map_err(|e| MiniPngError::FileRead {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    let blocks = RawBlocks::new(&bytes)?;
+    if hex {
+        println!("  00000000  {:<48} magic", "4d 69 6e 69 2d 50 4e 47");
+    }
+    // blocks before a broken one are still listed
+    for block in blocks {
+        let block = block?;
+        println!(
+            "#{} '{}' at {:#x}, {} bytes: {}",
+            block.index,
+            (block.block_type as char).escape_default(),
+            block.offset,
+            block.content.len(),
+            block.preview()
+        );
+        if hex {
+            print!("{}", block.hex_dump());
+        }
+    }
+
+    Ok(())
+}
+
+fn validate(paths: &[PathBuf], json: bool) -> Result<()> {
+    let reports: Vec<(String, Report)> = paths
+        .iter()
+        .map(|path| {
+            let report = match fs::read(path) {
+                Ok(bytes) => minipng::validate(&bytes),
+                Err(e) => Report {
+                    error: Some(MiniPngError::FileRead {
+                        path: path.display().to_string(),
+                        source: e,
+                    }),
+                    findings: Vec::new(),
+                },
+            };
+            (path.display().to_string(), report)
+        })
+        .collect();
+
+    if json {
+        let objects: Vec<String> = reports
+            .iter()
+            .map(|(path, report)| report.to_json(path))
+            .collect();
+        println!("[{}]", objects.join(","));
+    } else {
+        for (path, report) in &reports {
+            match &report.error {
+                None => println!("{}: ok", path),
+                Some(e) => println!("{}: FAIL: {}", path, e),
+            }
+            for finding in &report.findings {
+                println!("  - {}", finding);
+            }
+        }
+    }
+
+    let failed = reports.iter().filter(|(_, r)| !r.is_valid()).count();
+    if failed > 0 {
+        return Err(MiniPngError::ValidationFailed {
+            failed,
+            total: reports.len(),
+        });
     }
 
     Ok(())
@@ -1,305 +1,302 @@
-use std::{fmt::Debug, path::PathBuf};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::PathBuf,
+};
 
 use crate::error::{MiniPngError, Result};
+use crate::options::{DecodeOptions, Warning};
+use crate::pixel::Pixel;
 use crate::pixel_type::PixelType;
+use crate::reader::MiniPngReader;
+use crate::render::RenderOptions;
+use crate::writer::MiniPngWriter;
+
+/// Characters of the white and black pixels in the text read by
+/// [`MiniPNG::bw_from_string_with`] and written by [`MiniPNG::bw_to_string_with`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BwChars {
+    /// Character of the white pixels.
+    pub white: char,
+    /// Character of the black pixels.
+    pub black: char,
+}
 
-#[derive(Debug, Clone)]
+impl Default for BwChars {
+    fn default() -> Self {
+        BwChars {
+            white: 'X',
+            black: ' ',
+        }
+    }
+}
+
+/// Content of the `H` block.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Header {
+    /// Width of the image, in pixels.
     pub width: u32,
+    /// Height of the image, in pixels.
     pub height: u32,
+    /// Encoding of the pixels in the data blocks.
     pub pixel_type: PixelType,
 }
 
-#[derive(Debug, Clone)]
-pub struct Palette {
-    pub colors: Vec<[u8; 3]>,
-}
-
-#[derive(Debug)]
-pub struct MiniPNG {
-    pub header: Header,
-    pub palette: Option<Palette>,
-    pub data: Vec<u8>,
-    pub comments: Vec<String>,
-}
-
-impl MiniPNG {
-    fn display_black_and_white(&self) -> Result<String> {
-        let mut output = String::new();
-        let total_pixels = (self.header.width * self.header.height) as usize;
-        for i in 0..total_pixels {
-            let byte = self.data[i / 8];
-
-            if i % self.header.width as usize == 0 {
-                output.push('\n');
-            }
+impl Header {
+    /// Number of pixels of an image with this header.
+    pub fn pixel_count(&self) -> Result<usize> {
+        (self.width as usize)
+            .checked_mul(self.height as usize)
+            .ok_or(MiniPngError::DimensionsOverflow {
+                width: self.width,
+                height: self.height,
+            })
+    }
 
-            let pixel = (byte >> (7 - (i % 8))) & 1;
+    /// Number of bytes of pixel data needed by an image with this header.
+    pub fn data_size(&self) -> Result<usize> {
+        self.pixel_count()?
+            .checked_mul(self.pixel_type.bit_size())
+            .map(|bits| bits.div_ceil(8))
+            .ok_or(MiniPngError::DimensionsOverflow {
+                width: self.width,
+                height: self.height,
+            })
+    }
 
-            if pixel == 0 {
-                output.push(' '); // black
-            } else {
-                output.push('X'); // white
-            }
+    // parses the content of a `H` block
+    pub(crate) fn parse(content: &[u8]) -> Result<Self> {
+        if content.len() < 9 {
+            return Err(MiniPngError::HeaderTooSmall);
         }
 
-        Ok(output)
+        Ok(Header {
+            width: u32::from_be_bytes([content[0], content[1], content[2], content[3]]),
+            height: u32::from_be_bytes([content[4], content[5], content[6], content[7]]),
+            pixel_type: content[8].try_into()?,
+        })
     }
 
-    fn display_grey_levels(&self) -> Result<String> {
-        let mut output = String::new();
-        for (i, pixel) in self.data.iter().enumerate() {
-            if i % self.header.width as usize == 0 {
-                output.push('\n');
-            }
-
-            output.push_str(&format!(
-                "\x1b[38;2;{:?};{:?};{:?}m██\x1b[0m",
-                pixel, pixel, pixel
-            ));
+    pub(crate) fn check_data_size(&self, found: usize) -> Result<()> {
+        let expected = self.data_size()?;
+        if found != expected {
+            return Err(MiniPngError::DataSizeMismatch {
+                expected: expected.saturating_mul(8),
+                found: found.saturating_mul(8),
+                width: self.width,
+                height: self.height,
+            });
         }
-
-        Ok(output)
+        Ok(())
     }
+}
 
-    fn display_rgb(&self) -> Result<String> {
-        let mut output = String::new();
-        for (i, pixel) in self.data.chunks(3).enumerate() {
-            if i % self.header.width as usize == 0 {
-                output.push('\n');
-            }
+/// Content of the `P` block, as a list of RGB colors.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// RGB colors, pixels refer to them by index.
+    pub colors: Vec<[u8; 3]>,
+}
 
-            output.push_str(&format!(
-                "\x1b[38;2;{:?};{:?};{:?}m██\x1b[0m",
-                pixel[0], pixel[1], pixel[2]
-            ));
+impl Palette {
+    // parses the content of a `P` block, trailing bytes that don't form a
+    // whole color are ignored
+    pub(crate) fn parse(content: &[u8]) -> Self {
+        Palette {
+            colors: content
+                .chunks_exact(3)
+                .map(|chunk| [chunk[0], chunk[1], chunk[2]])
+                .collect(),
         }
-
-        Ok(output)
     }
+}
 
-    fn display_palette(&self) -> Result<String> {
-        let mut output = String::new();
-        let palette = self.palette.as_ref().ok_or(MiniPngError::MissingPalette)?;
-
-        for (i, &index) in self.data.iter().enumerate() {
-            if i % self.header.width as usize == 0 {
-                output.push('\n');
-            }
-
-            let color = palette
-                .colors
-                .get(index as usize)
-                .ok_or(MiniPngError::InvalidPaletteIndex(index))?;
-            output.push_str(&format!(
-                "\x1b[38;2;{:?};{:?};{:?}m██\x1b[0m",
-                color[0], color[1], color[2]
-            ));
-        }
+/// A decoded Mini-PNG image.
+///
+/// `data` holds the concatenation of every `D` block, packed according to
+/// `header.pixel_type` without any padding between rows.
+#[derive(Debug, Clone)]
+pub struct MiniPNG {
+    /// Content of the `H` block.
+    pub header: Header,
+    /// Content of the `P` block, only found in palette images.
+    pub palette: Option<Palette>,
+    /// Packed pixel data of every `D` block.
+    pub data: Vec<u8>,
+    /// Text of every `C` block in file order.
+    pub comments: Vec<String>,
+}
 
-        Ok(output)
+impl MiniPNG {
+    /// Creates a black and white image from a string.
+    ///
+    /// The string should contain 'X' (white) and ' ' (black), shorter lines
+    /// are padded with black. Any other character than newlines, 'X' and ' '
+    /// returns [`MiniPngError::IllegalCharacter`].
+    pub fn bw_from_string(input: &str) -> Result<Self> {
+        Self::bw_from_string_with(input, BwChars::default())
     }
 
-    // this function creates a MiniPNG image from a string
-    // this string should contain 'X' (white) and ' ' (black)
-    // all other token than newline chars, 'X' and ' '
-    // will return an Err
-    pub fn bw_from_string(input: &str) -> Result<Self> {
-        let height = input.lines().count() as u32;
-        let width = input
-            .lines()
-            .map(|line| line.chars().count())
-            .max()
-            .unwrap_or(0) as u32;
+    /// Same as [`MiniPNG::bw_from_string`] with custom characters for white
+    /// and black pixels.
+    pub fn bw_from_string_with(input: &str, chars: BwChars) -> Result<Self> {
+        let height = dimension("height", input.lines().count())?;
+        let width = dimension(
+            "width",
+            input
+                .lines()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0),
+        )?;
+        let header = Header {
+            width,
+            height,
+            pixel_type: PixelType::BlackAndWhite,
+        };
 
-        let total_pixels = (width * height) as usize;
-        let mut data = vec![0x0; (total_pixels + 7) / 8];
-        let lines: Vec<Vec<_>> = input.lines().map(|line| line.bytes().collect()).collect();
+        let total_pixels = header.pixel_count()?;
+        let mut data = vec![0x0; total_pixels.div_ceil(8)];
+        let lines: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
 
         for i in 0..total_pixels {
             let c = lines[i / width as usize]
                 .get(i % width as usize)
                 .copied()
-                .unwrap_or(b' ');
+                .unwrap_or(chars.black);
 
             let bit: u8 = match c {
-                b' ' => 0, // black
-                b'X' => 1, // white
-                _ => return Err(MiniPngError::IllegalCharacter(c as char)),
+                c if c == chars.white => 1,
+                c if c == chars.black => 0,
+                _ => return Err(MiniPngError::IllegalCharacter(c)),
             };
 
             data[i / 8] |= bit << (7 - (i % 8));
         }
 
         Ok(MiniPNG {
-            header: Header {
-                width,
-                height,
-                pixel_type: PixelType::BlackAndWhite,
-            },
+            header,
             palette: None,
             data,
             comments: Vec::new(),
         })
     }
 
-    pub fn save(&self, path: PathBuf) -> Result<()> {
-        let mut bytes = Vec::new();
-
-        bytes.extend_from_slice(b"Mini-PNG");
-
-        // header
-        bytes.push(b'H');
-        bytes.extend_from_slice(&9u32.to_be_bytes());
-        bytes.extend_from_slice(&self.header.width.to_be_bytes());
-        bytes.extend_from_slice(&self.header.height.to_be_bytes());
-        bytes.push(self.header.pixel_type as u8);
-
-        // palette
-        if let Some(palette) = &self.palette {
-            bytes.push(b'P');
-            bytes.extend_from_slice(&(palette.colors.len() as u32).to_be_bytes());
+    /// Turns a black and white image back into the text read by
+    /// [`MiniPNG::bw_from_string`], one line per row.
+    pub fn bw_to_string(&self) -> Result<String> {
+        self.bw_to_string_with(BwChars::default())
+    }
 
-            for color in &palette.colors {
-                bytes.extend_from_slice(color);
-            }
+    /// Same as [`MiniPNG::bw_to_string`] with custom characters for white
+    /// and black pixels.
+    pub fn bw_to_string_with(&self, chars: BwChars) -> Result<String> {
+        if self.header.pixel_type != PixelType::BlackAndWhite {
+            return Err(MiniPngError::PixelTypeMismatch {
+                expected: PixelType::BlackAndWhite,
+                found: self.header.pixel_type,
+            });
         }
 
-        // comments
-        for comment in &self.comments {
-            bytes.push(b'C');
-            bytes.extend_from_slice(&(comment.len() as u32).to_be_bytes());
-            bytes.extend_from_slice(comment.as_bytes());
+        let mut output = String::new();
+        for row in self.rows()? {
+            for pixel in row {
+                output.push(match pixel {
+                    Pixel::Bw(true) => chars.white,
+                    _ => chars.black,
+                });
+            }
+            output.push('\n');
         }
+        Ok(output)
+    }
 
-        // data
-        bytes.push(b'D');
-        bytes.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
-        bytes.extend_from_slice(&self.data);
-
-        std::fs::write(&path, bytes).map_err(|e| MiniPngError::FileWrite {
+    /// Writes the image to `path` as a Mini-PNG file.
+    pub fn save(&self, path: PathBuf) -> Result<()> {
+        let file = File::create(&path).map_err(|e| MiniPngError::FileWrite {
             path: path.display().to_string(),
             source: e,
-        })
+        })?;
+
+        self.write_to(BufWriter::new(file))
+            .map(|_| ())
+            .map_err(|e| match e {
+                MiniPngError::Io(source) => MiniPngError::FileWrite {
+                    path: path.display().to_string(),
+                    source,
+                },
+                e => e,
+            })
     }
-}
 
-impl TryFrom<Vec<u8>> for MiniPNG {
-    type Error = MiniPngError;
+    /// Encodes the image as a Mini-PNG stream, see [`MiniPngWriter`].
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<W> {
+        self.header.check_data_size(self.data.len())?;
 
-    // this is the main minipng parsing function
-    // it takes a data Vec or u8 and turns it into
-    // a MiniPNG according to the specs
-    fn try_from(bytes: Vec<u8>) -> Result<Self> {
-        if bytes.len() < 8 {
-            return Err(MiniPngError::FileTooSmall);
-        }
-        if &bytes[0..8] != b"Mini-PNG" {
-            return Err(MiniPngError::InvalidMagicNumber);
+        let mut writer =
+            MiniPngWriter::new(writer, &self.header, self.palette.as_ref(), &self.comments)?;
+        for y in 0..self.header.height as usize {
+            writer.write_row(&self.packed_row(y))?;
         }
-        //  skip the "Mini-PNG" magic flag
-        let mut iter = bytes.iter().skip(8);
-
-        let mut header: Option<Header> = None;
-        let mut palette: Option<Palette> = None;
-        let mut data: Vec<u8> = Vec::new();
-        let mut comments: Vec<String> = Vec::new();
-
-        while let Some(&block_type) = iter.next() {
-            let length = u32::from_be_bytes([
-                *iter.next().ok_or(MiniPngError::InvalidBlockLength)?,
-                *iter.next().ok_or(MiniPngError::InvalidBlockLength)?,
-                *iter.next().ok_or(MiniPngError::InvalidBlockLength)?,
-                *iter.next().ok_or(MiniPngError::InvalidBlockLength)?,
-            ]);
-            let content: Vec<u8> = iter.by_ref().take(length as usize).copied().collect();
-
-            if content.len() != length as usize {
-                return Err(MiniPngError::BlockLengthMismatch);
-            }
-
-            match block_type {
-                b'C' => {
-                    // this allows for utf8 encoded comments which is not explicitly supported by
-                    // the spec which refers to ascii encoded comments
-                    let text =
-                        String::from_utf8(content).map_err(|_| MiniPngError::InvalidUtf8Comment)?;
-                    comments.push(text);
-                }
-
-                b'D' => {
-                    data.extend(content);
-                }
-
-                b'H' => {
-                    if header.is_some() {
-                        return Err(MiniPngError::DuplicateHeader);
-                    }
-                    if content.len() < 9 {
-                        return Err(MiniPngError::HeaderTooSmall);
-                    }
-
-                    header = Some(Header {
-                        width: u32::from_be_bytes([content[0], content[1], content[2], content[3]]),
-                        height: u32::from_be_bytes([
-                            content[4], content[5], content[6], content[7],
-                        ]),
-                        pixel_type: content[8].try_into()?,
-                    });
-                }
+        writer.finish()
+    }
 
-                b'P' => {
-                    if palette.is_some() {
-                        return Err(MiniPngError::DuplicatePalette);
-                    }
-                    palette = Some(Palette {
-                        colors: content
-                            .chunks_exact(3)
-                            .map(|chunk| [chunk[0], chunk[1], chunk[2]])
-                            .collect(),
-                    });
+    // returns the row `y` in the layout expected by `MiniPngWriter::write_row`
+    pub(crate) fn packed_row(&self, y: usize) -> Vec<u8> {
+        let width = self.header.width as usize;
+        match self.header.pixel_type {
+            PixelType::BlackAndWhite => {
+                let mut row = vec![0u8; width.div_ceil(8)];
+                for x in 0..width {
+                    let i = y * width + x;
+                    let bit = (self.data[i / 8] >> (7 - (i % 8))) & 1;
+                    row[x / 8] |= bit << (7 - (x % 8));
                 }
-
-                _ => return Err(MiniPngError::InvalidBlockType(block_type as char)),
+                row
+            }
+            pixel_type => {
+                let row_size = width * pixel_type.bit_size() / 8;
+                self.data[y * row_size..(y + 1) * row_size].to_vec()
             }
         }
+    }
+}
 
-        let header = header.ok_or(MiniPngError::MissingHeader)?;
-
-        if data.is_empty() {
-            return Err(MiniPngError::MissingData);
-        }
+impl TryFrom<Vec<u8>> for MiniPNG {
+    type Error = MiniPngError;
 
-        match header.pixel_type {
-            PixelType::Palette if palette.is_none() => {
-                return Err(MiniPngError::MissingPalette);
-            }
-            _ if palette.is_some() && header.pixel_type != PixelType::Palette => {
-                return Err(MiniPngError::UnexpectedPalette);
-            }
-            _ => {}
-        }
+    // this is the main minipng parsing function
+    // it takes a data Vec or u8 and turns it into
+    // a MiniPNG according to the specs, block by block
+    // through a MiniPngReader
+    fn try_from(bytes: Vec<u8>) -> Result<Self> {
+        MiniPngReader::new(bytes.as_slice())?.read_image()
+    }
+}
 
-        let bit_count = data.len() * 8;
+impl MiniPNG {
+    /// Decodes a Mini-PNG image from a stream, see [`MiniPngReader`].
+    pub fn read_from<R: Read>(reader: R) -> Result<Self> {
+        MiniPngReader::new(reader)?.read_image()
+    }
 
-        #[rustfmt::skip]
-        let expected_bit_count = (
-            (
-                header.width as usize
-                * header.height as usize
-                * header.pixel_type.bit_size()
-                + 7
-            ) / 8
-        ) * 8;
+    /// Decodes a Mini-PNG image with custom options, returning the warnings
+    /// raised in lenient mode along with the image.
+    pub fn read_with_options<R: Read>(
+        reader: R,
+        options: DecodeOptions,
+    ) -> Result<(Self, Vec<Warning>)> {
+        MiniPngReader::with_options(reader, options)?.read_image_with_warnings()
+    }
 
-        if expected_bit_count != bit_count {
-            return Err(MiniPngError::DataSizeMismatch {
-                expected: expected_bit_count,
-                found: bit_count,
-                width: header.width,
-                height: header.height,
-            });
-        }
+    pub(crate) fn from_parts(
+        header: Option<Header>,
+        palette: Option<Palette>,
+        data: Vec<u8>,
+        comments: Vec<String>,
+    ) -> Result<Self> {
+        let header = check_blocks(header, palette.is_some(), data.len())?;
 
         Ok(MiniPNG {
             header,
@@ -310,34 +307,100 @@ impl TryFrom<Vec<u8>> for MiniPNG {
     }
 }
 
-impl MiniPNG {
-    pub fn display(&self) -> Result<String> {
-        let mut output = String::new();
-        output.push_str("Mini-PNG Image\n");
-        output.push_str(&format!("Width: {}\n", self.header.width));
-        output.push_str(&format!("Height: {}\n", self.header.height));
-        output.push_str(&format!("Pixel Type: {}\n", self.header.pixel_type));
-        output.push_str(&format!("Data size: {} bytes\n", self.data.len()));
-
-        if let Some(palette) = &self.palette {
-            output.push_str(&format!("Palette: {} colors\n", palette.colors.len()));
-        }
+// converts a size computed from text or another format to a header dimension
+pub(crate) fn dimension(name: &'static str, value: usize) -> Result<u32> {
+    u32::try_from(value).map_err(|_| MiniPngError::LimitExceeded {
+        limit: name,
+        value: value as u64,
+        max: u32::MAX as u64,
+    })
+}
 
-        if !self.comments.is_empty() {
-            output.push_str("Comments:\n");
-            for comment in &self.comments {
-                output.push_str(&format!("  - {}\n", comment));
-            }
+// checks that the blocks found in a file form a valid image, this is shared
+// by every parser once all the blocks have been read
+pub(crate) fn check_blocks(
+    header: Option<Header>,
+    has_palette: bool,
+    data_size: usize,
+) -> Result<Header> {
+    let header = header.ok_or(MiniPngError::MissingHeader)?;
+
+    if data_size == 0 {
+        return Err(MiniPngError::MissingData);
+    }
+
+    match header.pixel_type {
+        PixelType::Palette if !has_palette => {
+            return Err(MiniPngError::MissingPalette);
         }
+        _ if has_palette && header.pixel_type != PixelType::Palette => {
+            return Err(MiniPngError::UnexpectedPalette);
+        }
+        _ => {}
+    }
 
-        let image_data = match self.header.pixel_type {
-            PixelType::BlackAndWhite => self.display_black_and_white()?,
-            PixelType::GrayLevels => self.display_grey_levels()?,
-            PixelType::RGB => self.display_rgb()?,
-            PixelType::Palette => self.display_palette()?,
-        };
+    header.check_data_size(data_size)?;
+
+    Ok(header)
+}
+
+impl MiniPNG {
+    /// Returns a human readable summary of the image followed by its pixels.
+    pub fn display(&self) -> Result<String> {
+        self.display_with(&RenderOptions::default())
+    }
 
+    /// Same as [`MiniPNG::display`] with the pixels drawn by
+    /// [`MiniPNG::render`] with `options`. The lines of the summary are taken
+    /// from the ones of `options.fit`.
+    pub fn display_with(&self, options: &RenderOptions) -> Result<String> {
+        self.header.check_data_size(self.data.len())?;
+
+        let mut output = summary(
+            &self.header,
+            self.data.len(),
+            self.palette.as_ref().map(|palette| palette.colors.len()),
+            &self.comments,
+        );
+
+        // the summary and the empty line starting the pixels
+        let used = output.lines().count() as u32 + 1;
+        let options = RenderOptions {
+            fit: options
+                .fit
+                .map(|(columns, lines)| (columns, lines.saturating_sub(used).max(1))),
+            ..options.clone()
+        };
+        let image_data = self.render(&options)?;
         output.push_str(&image_data);
         Ok(output)
     }
 }
+
+// human readable summary shared by `MiniPNG::display` and `ImageInfo`
+pub(crate) fn summary(
+    header: &Header,
+    data_size: usize,
+    palette_size: Option<usize>,
+    comments: &[String],
+) -> String {
+    let mut output = String::new();
+    output.push_str("Mini-PNG Image\n");
+    output.push_str(&format!("Width: {}\n", header.width));
+    output.push_str(&format!("Height: {}\n", header.height));
+    output.push_str(&format!("Pixel Type: {}\n", header.pixel_type));
+    output.push_str(&format!("Data size: {} bytes\n", data_size));
+
+    if let Some(size) = palette_size {
+        output.push_str(&format!("Palette: {} colors\n", size));
+    }
+
+    if !comments.is_empty() {
+        output.push_str("Comments:\n");
+        for comment in comments {
+            output.push_str(&format!("  - {}\n", comment));
+        }
+    }
+
+    output
+}
@@ -1,7 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use crate::error::MiniPngError;
-    use crate::mininpng::MiniPNG;
+    use crate::{BwChars, MiniPNG, MiniPngError, PixelType};
 
     #[test]
     fn test_bw_from_string_all_white() {
@@ -301,4 +300,62 @@ XXXXX
             expected
         );
     }
+
+    #[test]
+    fn test_bw_to_string_round_trips() {
+        for input in ["XXX\nX X\n   \n", "X  \n\n  X\n", "", " X \n"] {
+            let image = MiniPNG::bw_from_string(input).unwrap();
+            let text = image.bw_to_string().unwrap();
+            // shorter lines are padded with black
+            let expected: String = input
+                .lines()
+                .map(|line| format!("{:<1$}\n", line, image.header.width as usize))
+                .collect();
+            assert_eq!(text, expected);
+            assert_eq!(MiniPNG::bw_from_string(&text).unwrap().data, image.data);
+        }
+
+        let sample =
+            MiniPNG::read_from(include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice()).unwrap();
+        let decoded = MiniPNG::bw_from_string(&sample.bw_to_string().unwrap()).unwrap();
+        assert!(decoded.pixels().unwrap().eq(sample.pixels().unwrap()));
+    }
+
+    #[test]
+    fn test_bw_string_custom_chars() {
+        let chars = BwChars {
+            white: '#',
+            black: '.',
+        };
+        let image = MiniPNG::bw_from_string_with("#.\n.", chars).unwrap();
+        assert_eq!(image.bw_to_string_with(chars).unwrap(), "#.\n..\n");
+        assert_eq!(image.bw_to_string().unwrap(), "X \n  \n");
+
+        assert!(matches!(
+            MiniPNG::bw_from_string_with("#X", chars),
+            Err(MiniPngError::IllegalCharacter('X'))
+        ));
+
+        // multi-byte characters count as a single pixel
+        let blocks = BwChars {
+            white: '█',
+            black: '░',
+        };
+        let image = MiniPNG::bw_from_string_with("█░█", blocks).unwrap();
+        assert_eq!(image.header.width, 3);
+    }
+
+    #[test]
+    fn test_bw_to_string_requires_black_and_white() {
+        let image =
+            MiniPNG::read_from(include_bytes!("../minipng-samples/other/ok/gray.mp").as_slice())
+                .unwrap();
+        assert!(matches!(
+            image.bw_to_string(),
+            Err(MiniPngError::PixelTypeMismatch {
+                expected: PixelType::BlackAndWhite,
+                found: PixelType::GrayLevels,
+            })
+        ));
+    }
 }
@@ -0,0 +1,170 @@
+use std::fmt::Display;
+
+use crate::limits::DecodeLimits;
+
+/// How the decoder reacts to a malformed file.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Strictness {
+    /// Any deviation from the format is an error.
+    #[default]
+    Strict,
+    /// Recoverable problems are fixed and reported as [`Warning`]s.
+    Lenient,
+}
+
+/// Options of the Mini-PNG decoder.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DecodeOptions {
+    /// How malformed files are handled.
+    pub strictness: Strictness,
+    /// Bounds on the sizes read from the file.
+    pub limits: DecodeLimits,
+}
+
+impl DecodeOptions {
+    /// Lenient decoding with the default limits.
+    pub fn lenient() -> Self {
+        DecodeOptions {
+            strictness: Strictness::Lenient,
+            ..DecodeOptions::default()
+        }
+    }
+}
+
+/// A problem worked around by the decoder in [`Strictness::Lenient`] mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A block with an unknown type was skipped.
+    UnknownBlock {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+        /// Type byte of the block.
+        block_type: u8,
+    },
+    /// The stream ended in the middle of a block length field.
+    TruncatedLength {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+        /// Type byte of the block.
+        block_type: u8,
+    },
+    /// The stream ended in the middle of a block, the bytes found are kept
+    /// for data blocks and dropped otherwise.
+    TruncatedBlock {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+        /// Type byte of the block.
+        block_type: u8,
+        /// Length read from the block.
+        declared: u32,
+        /// Bytes left in the stream after the length field.
+        remaining: usize,
+    },
+    /// Invalid UTF-8 sequences of a comment were replaced.
+    InvalidUtf8Comment {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+    },
+    /// A second header block was ignored.
+    DuplicateHeader {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+    },
+    /// A second palette block was ignored.
+    DuplicatePalette {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Offset of the type byte from the start of the file.
+        offset: u64,
+    },
+    /// A palette was found in an image whose pixel type is not palette.
+    IgnoredPalette,
+    /// The pixel data was too short and was padded with zeros.
+    PaddedData {
+        /// Number of zero bytes added.
+        missing: usize,
+    },
+    /// The pixel data was too long and was truncated.
+    TruncatedData {
+        /// Number of bytes dropped.
+        extra: usize,
+    },
+    /// Pixels referred to colors outside of the palette, which was extended with black.
+    PaddedPalette {
+        /// Number of black colors added.
+        added: usize,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnknownBlock {
+                index,
+                offset,
+                block_type,
+            } => write!(
+                f,
+                "block #{} ('{}') at offset {:#x}: unknown block type, skipped",
+                index, *block_type as char, offset
+            ),
+            Warning::TruncatedLength {
+                index,
+                offset,
+                block_type,
+            } => write!(
+                f,
+                "block #{} ('{}') at offset {:#x}: truncated length field, skipped",
+                index, *block_type as char, offset
+            ),
+            Warning::TruncatedBlock {
+                index,
+                offset,
+                block_type,
+                declared,
+                remaining,
+            } => write!(
+                f,
+                "block #{} ('{}') at offset {:#x}: declared length {} but only {} bytes remain",
+                index, *block_type as char, offset, declared, remaining
+            ),
+            Warning::InvalidUtf8Comment { index, offset } => write!(
+                f,
+                "block #{} ('C') at offset {:#x}: invalid UTF-8 replaced",
+                index, offset
+            ),
+            Warning::DuplicateHeader { index, offset } => write!(
+                f,
+                "block #{} ('H') at offset {:#x}: second header ignored",
+                index, offset
+            ),
+            Warning::DuplicatePalette { index, offset } => write!(
+                f,
+                "block #{} ('P') at offset {:#x}: second palette ignored",
+                index, offset
+            ),
+            Warning::IgnoredPalette => {
+                write!(f, "palette ignored as the pixel type is not palette")
+            }
+            Warning::PaddedData { missing } => {
+                write!(f, "pixel data padded with {} zero bytes", missing)
+            }
+            Warning::TruncatedData { extra } => {
+                write!(f, "{} extra bytes of pixel data dropped", extra)
+            }
+            Warning::PaddedPalette { added } => {
+                write!(f, "palette extended with {} black colors", added)
+            }
+        }
+    }
+}
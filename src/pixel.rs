@@ -0,0 +1,156 @@
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::{Header, MiniPNG, Palette};
+use crate::pixel_type::PixelType;
+
+/// A single pixel, its variant matches the [`PixelType`] of the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pixel {
+    /// `true` is white, `false` is black.
+    Bw(bool),
+    /// Gray level, 0 is black and 255 is white.
+    Gray(u8),
+    /// Index in the palette of the image.
+    Index(u8),
+    /// Red, green and blue channels.
+    Rgb([u8; 3]),
+}
+
+impl Pixel {
+    /// Pixel type of the images this pixel can be stored in.
+    pub fn pixel_type(&self) -> PixelType {
+        match self {
+            Pixel::Bw(_) => PixelType::BlackAndWhite,
+            Pixel::Gray(_) => PixelType::GrayLevels,
+            Pixel::Index(_) => PixelType::Palette,
+            Pixel::Rgb(_) => PixelType::RGB,
+        }
+    }
+}
+
+impl MiniPNG {
+    /// Creates an image filled with zeros, which is black for every pixel
+    /// type but palette where it is the first color.
+    pub fn new(header: Header, palette: Option<Palette>) -> Result<Self> {
+        match header.pixel_type {
+            PixelType::Palette if palette.is_none() => return Err(MiniPngError::MissingPalette),
+            PixelType::Palette => {}
+            _ if palette.is_some() => return Err(MiniPngError::UnexpectedPalette),
+            _ => {}
+        }
+
+        Ok(MiniPNG {
+            data: vec![0; header.data_size()?],
+            header,
+            palette,
+            comments: Vec::new(),
+        })
+    }
+
+    /// Returns the pixel at column `x` of row `y`.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Result<Pixel> {
+        let i = self.pixel_index(x, y)?;
+        Ok(self.pixel_at(i))
+    }
+
+    /// Replaces the pixel at column `x` of row `y`.
+    ///
+    /// The pixel must match the pixel type of the image, palette indexes are
+    /// not checked against the palette.
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) -> Result<()> {
+        if pixel.pixel_type() != self.header.pixel_type {
+            return Err(MiniPngError::PixelTypeMismatch {
+                expected: self.header.pixel_type,
+                found: pixel.pixel_type(),
+            });
+        }
+
+        let i = self.pixel_index(x, y)?;
+        match pixel {
+            Pixel::Bw(white) => {
+                let mask = 1 << (7 - (i % 8));
+                if white {
+                    self.data[i / 8] |= mask;
+                } else {
+                    self.data[i / 8] &= !mask;
+                }
+            }
+            Pixel::Gray(value) | Pixel::Index(value) => self.data[i] = value,
+            Pixel::Rgb(color) => self.data[i * 3..i * 3 + 3].copy_from_slice(&color),
+        }
+
+        Ok(())
+    }
+
+    /// Iterates over every pixel, row after row.
+    ///
+    /// Fails if the data doesn't match the size announced by the header.
+    pub fn pixels(&self) -> Result<impl Iterator<Item = Pixel> + '_> {
+        let count = self.checked_pixel_count()?;
+        Ok((0..count).map(|i| self.pixel_at(i)))
+    }
+
+    /// Iterates over the rows of the image, from top to bottom.
+    ///
+    /// Fails if the data doesn't match the size announced by the header.
+    pub fn rows(&self) -> Result<impl Iterator<Item = Vec<Pixel>> + '_> {
+        self.checked_pixel_count()?;
+        let width = self.header.width as usize;
+
+        Ok((0..self.header.height as usize).map(move |y| {
+            (y * width..(y + 1) * width)
+                .map(|i| self.pixel_at(i))
+                .collect()
+        }))
+    }
+
+    /// Color of a pixel, palette indexes are resolved through the palette.
+    pub fn color_of(&self, pixel: Pixel) -> Result<[u8; 3]> {
+        match pixel {
+            Pixel::Bw(true) => Ok([255, 255, 255]),
+            Pixel::Bw(false) => Ok([0, 0, 0]),
+            Pixel::Gray(value) => Ok([value, value, value]),
+            Pixel::Rgb(color) => Ok(color),
+            Pixel::Index(index) => self
+                .palette
+                .as_ref()
+                .ok_or(MiniPngError::MissingPalette)?
+                .colors
+                .get(index as usize)
+                .copied()
+                .ok_or(MiniPngError::InvalidPaletteIndex(index)),
+        }
+    }
+
+    fn checked_pixel_count(&self) -> Result<usize> {
+        self.header.check_data_size(self.data.len())?;
+        self.header.pixel_count()
+    }
+
+    // position of a pixel in the row after row order, the data size is
+    // checked so that `pixel_at` can index it
+    fn pixel_index(&self, x: u32, y: u32) -> Result<usize> {
+        if x >= self.header.width || y >= self.header.height {
+            return Err(MiniPngError::PixelOutOfBounds {
+                x,
+                y,
+                width: self.header.width,
+                height: self.header.height,
+            });
+        }
+        self.header.check_data_size(self.data.len())?;
+
+        Ok(y as usize * self.header.width as usize + x as usize)
+    }
+
+    // decodes the `i`-th pixel, `i` must be in bounds
+    fn pixel_at(&self, i: usize) -> Pixel {
+        match self.header.pixel_type {
+            PixelType::BlackAndWhite => Pixel::Bw((self.data[i / 8] >> (7 - (i % 8))) & 1 == 1),
+            PixelType::GrayLevels => Pixel::Gray(self.data[i]),
+            PixelType::Palette => Pixel::Index(self.data[i]),
+            PixelType::RGB => {
+                Pixel::Rgb([self.data[i * 3], self.data[i * 3 + 1], self.data[i * 3 + 2]])
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::header;
+    use crate::{MiniPNG, MiniPngError, Palette, Pixel, PixelType};
+
+    #[test]
+    fn test_get_pixel_black_and_white_across_bytes() {
+        let image = MiniPNG::bw_from_string("XXXXXXXXXX\n          \nX        X").unwrap();
+
+        assert_eq!(image.get_pixel(9, 0).unwrap(), Pixel::Bw(true));
+        assert_eq!(image.get_pixel(0, 1).unwrap(), Pixel::Bw(false));
+        assert_eq!(image.get_pixel(0, 2).unwrap(), Pixel::Bw(true));
+        assert_eq!(image.get_pixel(5, 2).unwrap(), Pixel::Bw(false));
+        assert!(matches!(
+            image.get_pixel(10, 0),
+            Err(MiniPngError::PixelOutOfBounds {
+                x: 10,
+                y: 0,
+                width: 10,
+                height: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_set_pixel_every_pixel_type() {
+        let mut bw = MiniPNG::new(header(3, 3, PixelType::BlackAndWhite), None).unwrap();
+        bw.set_pixel(2, 2, Pixel::Bw(true)).unwrap();
+        bw.set_pixel(0, 0, Pixel::Bw(true)).unwrap();
+        bw.set_pixel(0, 0, Pixel::Bw(false)).unwrap();
+        assert_eq!(bw.data, vec![0x00, 0x80]);
+
+        let mut rgb = MiniPNG::new(header(2, 2, PixelType::RGB), None).unwrap();
+        rgb.set_pixel(1, 1, Pixel::Rgb([1, 2, 3])).unwrap();
+        assert_eq!(&rgb.data[9..], &[1, 2, 3]);
+        assert_eq!(rgb.get_pixel(1, 1).unwrap(), Pixel::Rgb([1, 2, 3]));
+
+        let palette = Palette {
+            colors: vec![[0, 0, 0], [255, 0, 0]],
+        };
+        let mut indexed = MiniPNG::new(header(2, 1, PixelType::Palette), Some(palette)).unwrap();
+        indexed.set_pixel(1, 0, Pixel::Index(1)).unwrap();
+        assert_eq!(
+            indexed.color_of(indexed.get_pixel(1, 0).unwrap()).unwrap(),
+            [255, 0, 0]
+        );
+
+        assert!(matches!(
+            indexed.set_pixel(0, 0, Pixel::Gray(3)),
+            Err(MiniPngError::PixelTypeMismatch {
+                expected: PixelType::Palette,
+                found: PixelType::GrayLevels
+            })
+        ));
+    }
+
+    #[test]
+    fn test_rows_and_pixels() {
+        let bytes = include_bytes!("../minipng-samples/other/ok/french-flag.mp");
+        let image = MiniPNG::read_from(&bytes[..]).unwrap();
+
+        let rows: Vec<Vec<Pixel>> = image.rows().unwrap().collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3].len(), 6);
+        assert_eq!(rows[3][0], Pixel::Rgb([0, 0, 255]));
+        assert_eq!(rows[3][2], Pixel::Rgb([255, 255, 255]));
+        assert_eq!(rows[3][5], Pixel::Rgb([255, 0, 0]));
+
+        assert_eq!(image.pixels().unwrap().count(), 24);
+        assert_eq!(
+            image.pixels().unwrap().nth(7).unwrap(),
+            image.get_pixel(1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pixels_checks_data_size() {
+        let mut image = MiniPNG::new(header(4, 4, PixelType::GrayLevels), None).unwrap();
+        image.data.pop();
+
+        assert!(matches!(
+            image.pixels().map(|pixels| pixels.count()),
+            Err(MiniPngError::DataSizeMismatch { .. })
+        ));
+        assert!(image.get_pixel(3, 3).is_err());
+    }
+}
@@ -2,12 +2,18 @@ use std::fmt::Display;
 
 use crate::error::MiniPngError;
 
+/// Pixel encoding declared by the header block.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum PixelType {
-    BlackAndWhite, // 0 = black-and-white
-    GrayLevels,    // 1 = gray levels
-    Palette,       // 2 = palette
-    RGB,           // 3 = 24 bits color
+    /// 0, one bit per pixel, 1 for white.
+    BlackAndWhite,
+    /// 1, one byte of gray level per pixel.
+    GrayLevels,
+    /// 2, one byte per pixel indexing the palette.
+    Palette,
+    /// 3, three bytes of red, green and blue per pixel.
+    RGB,
 }
 
 impl TryFrom<u8> for PixelType {
@@ -36,6 +42,7 @@ impl Display for PixelType {
 }
 
 impl PixelType {
+    /// Number of bits used by a single pixel in the data blocks.
     pub fn bit_size(&self) -> usize {
         match self {
             PixelType::BlackAndWhite => 1,
@@ -0,0 +1,432 @@
+use std::io::{Read, Write};
+
+use crate::error::{MiniPngError, Result};
+use crate::limits::DecodeLimits;
+use crate::mininpng::{Header, MiniPNG, Palette};
+use crate::pixel_type::PixelType;
+use crate::zlib;
+
+pub(crate) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// compressed image data is split in IDAT chunks of at most this size
+const MAX_IDAT_SIZE: usize = 1 << 20;
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+// CRC-32 of `data`, as stored at the end of each PNG chunk
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+impl MiniPNG {
+    /// Encodes the image as a PNG file.
+    ///
+    /// Black and white images become 1 bit grayscale, gray levels 8 bit
+    /// grayscale, palette images 8 bit indexed color and RGB images 8 bit
+    /// truecolor. Comments are stored in `tEXt` chunks with the `Comment`
+    /// keyword, or in `iTXt` chunks when they are not Latin-1.
+    pub fn to_png<W: Write>(&self, mut writer: W) -> Result<W> {
+        self.header.check_data_size(self.data.len())?;
+        if self.header.width == 0 || self.header.height == 0 {
+            return Err(MiniPngError::UnsupportedPng("PNG images can't be empty"));
+        }
+
+        let (bit_depth, color_type) = match self.header.pixel_type {
+            PixelType::BlackAndWhite => (1, 0),
+            PixelType::GrayLevels => (8, 0),
+            PixelType::Palette => (8, 3),
+            PixelType::RGB => (8, 2),
+        };
+
+        writer.write_all(&SIGNATURE).map_err(MiniPngError::Io)?;
+
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend(self.header.width.to_be_bytes());
+        ihdr.extend(self.header.height.to_be_bytes());
+        // no interlacing, the only compression and filter methods
+        ihdr.extend([bit_depth, color_type, 0, 0, 0]);
+        write_chunk(&mut writer, b"IHDR", &ihdr)?;
+
+        for comment in &self.comments {
+            let latin1: Option<Vec<u8>> = comment.chars().map(|c| u8::try_from(c).ok()).collect();
+            match latin1 {
+                Some(text) => {
+                    write_chunk(&mut writer, b"tEXt", &[b"Comment\0", &text[..]].concat())?
+                }
+                // keyword, no compression, empty language tag and translated keyword
+                None => write_chunk(
+                    &mut writer,
+                    b"iTXt",
+                    &[b"Comment\0\0\0\0\0", comment.as_bytes()].concat(),
+                )?,
+            }
+        }
+
+        if let Some(palette) = &self.palette {
+            let colors = &palette.colors[..palette.colors.len().min(256)];
+            if let Some(index) = self.data.iter().find(|&&i| i as usize >= colors.len()) {
+                return Err(MiniPngError::InvalidPaletteIndex(*index));
+            }
+            write_chunk(&mut writer, b"PLTE", colors.as_flattened())?;
+        }
+
+        // each scanline starts with its filter type, always None
+        let mut scanlines = Vec::new();
+        for y in 0..self.header.height as usize {
+            scanlines.push(0);
+            scanlines.extend(self.packed_row(y));
+        }
+        for chunk in zlib::compress(&scanlines).chunks(MAX_IDAT_SIZE) {
+            write_chunk(&mut writer, b"IDAT", chunk)?;
+        }
+
+        write_chunk(&mut writer, b"IEND", &[])?;
+        writer.flush().map_err(MiniPngError::Io)?;
+        Ok(writer)
+    }
+}
+
+impl MiniPNG {
+    /// Decodes a non-interlaced PNG file.
+    ///
+    /// 1 bit grayscale becomes black and white, 2 to 8 bit grayscale gray
+    /// levels, 8 bit truecolor RGB and indexed color a palette image. Images
+    /// with an alpha channel, transparency, 16 bit samples or interlacing
+    /// fail with [`MiniPngError::UnsupportedPng`].
+    ///
+    /// Chunk CRCs are verified. `tEXt`, `zTXt` and `iTXt` chunks become
+    /// comments, other ancillary chunks are ignored.
+    pub fn from_png<R: Read>(reader: R) -> Result<Self> {
+        Self::from_png_with_limits(reader, DecodeLimits::default())
+    }
+
+    /// Same as [`MiniPNG::from_png`] with custom limits.
+    pub fn from_png_with_limits<R: Read>(mut reader: R, limits: DecodeLimits) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(MiniPngError::Io)?;
+
+        if !bytes.starts_with(&SIGNATURE) {
+            return Err(MiniPngError::InvalidPng("missing PNG signature"));
+        }
+
+        let mut ihdr = None;
+        let mut palette = None;
+        let mut idat = Vec::new();
+        let mut comments = Vec::new();
+        let mut ended = false;
+
+        let mut rest = &bytes[SIGNATURE.len()..];
+        while !ended {
+            let (chunk_type, content, next) = read_chunk(rest)?;
+            rest = next;
+
+            if ihdr.is_none() && &chunk_type != b"IHDR" {
+                return Err(MiniPngError::InvalidPng("first chunk is not IHDR"));
+            }
+            match &chunk_type {
+                b"IHDR" if ihdr.is_some() => {
+                    return Err(MiniPngError::InvalidPng("second IHDR chunk"));
+                }
+                b"IHDR" => ihdr = Some(Ihdr::parse(content)?),
+                b"PLTE" => {
+                    if content.len() % 3 != 0 || content.is_empty() || content.len() > 256 * 3 {
+                        return Err(MiniPngError::InvalidPng("invalid PLTE chunk length"));
+                    }
+                    palette = Some(Palette::parse(content));
+                }
+                b"IDAT" => idat.extend_from_slice(content),
+                b"IEND" => ended = true,
+                b"tRNS" => return Err(MiniPngError::UnsupportedPng("transparency")),
+                b"tEXt" | b"zTXt" | b"iTXt" => {
+                    comments.push(text_chunk(&chunk_type, content, &limits)?)
+                }
+                // ancillary chunks have a lowercase first letter
+                _ if chunk_type[0].is_ascii_lowercase() => {}
+                _ => return Err(MiniPngError::UnsupportedPng("unknown critical chunk")),
+            }
+        }
+
+        let Some(ihdr) = ihdr else {
+            return Err(MiniPngError::InvalidPng("missing IHDR chunk"));
+        };
+        limits.check_header(&ihdr.header)?;
+
+        let palette = match ihdr.header.pixel_type {
+            PixelType::Palette => Some(palette.ok_or(MiniPngError::MissingPalette)?),
+            // a suggested palette of a truecolor image is not needed
+            _ => None,
+        };
+
+        let width = ihdr.header.width as usize;
+        let samples_per_pixel = if ihdr.color_type == 2 { 3 } else { 1 };
+        let bits_per_pixel = samples_per_pixel * ihdr.bit_depth as usize;
+        let row_size = (width * bits_per_pixel).div_ceil(8);
+        let height = ihdr.header.height as usize;
+        let size = (row_size + 1)
+            .checked_mul(height)
+            .ok_or(MiniPngError::DimensionsOverflow {
+                width: ihdr.header.width,
+                height: ihdr.header.height,
+            })?;
+
+        let scanlines = zlib::decompress(&idat, size)?;
+        if scanlines.len() != size {
+            return Err(MiniPngError::InvalidPng(
+                "image data doesn't match the dimensions",
+            ));
+        }
+        let rows = unfilter(&scanlines, row_size, bits_per_pixel.div_ceil(8))?;
+
+        let mut image = MiniPNG::new(ihdr.header, palette)?;
+        image.comments = comments;
+        for (y, row) in rows.chunks(row_size).enumerate() {
+            for x in 0..width {
+                match (image.header.pixel_type, ihdr.bit_depth) {
+                    (PixelType::BlackAndWhite, _) => {
+                        let i = y * width + x;
+                        image.data[i / 8] |= ((row[x / 8] >> (7 - (x % 8))) & 1) << (7 - (i % 8));
+                    }
+                    (PixelType::RGB, _) => {
+                        let i = (y * width + x) * 3;
+                        image.data[i..i + 3].copy_from_slice(&row[x * 3..x * 3 + 3]);
+                    }
+                    (pixel_type, depth) => {
+                        let depth = depth as usize;
+                        let shift = 8 - depth - (x * depth) % 8;
+                        let value = (row[x * depth / 8] >> shift) & ((1 << depth) - 1) as u8;
+                        image.data[y * width + x] = match pixel_type {
+                            // gray levels are rescaled to 8 bits
+                            PixelType::GrayLevels => value * (255 / ((1 << depth) - 1) as u8),
+                            _ => value,
+                        };
+                    }
+                }
+            }
+        }
+
+        if let Some(palette) = &image.palette
+            && let Some(&index) = image
+                .data
+                .iter()
+                .find(|&&i| i as usize >= palette.colors.len())
+        {
+            return Err(MiniPngError::InvalidPaletteIndex(index));
+        }
+
+        Ok(image)
+    }
+}
+
+struct Ihdr {
+    header: Header,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Ihdr {
+    fn parse(content: &[u8]) -> Result<Self> {
+        if content.len() != 13 {
+            return Err(MiniPngError::InvalidPng("IHDR chunk must be 13 bytes long"));
+        }
+        let width = u32::from_be_bytes([content[0], content[1], content[2], content[3]]);
+        let height = u32::from_be_bytes([content[4], content[5], content[6], content[7]]);
+        let [bit_depth, color_type, compression, filter, interlace] = [
+            content[8],
+            content[9],
+            content[10],
+            content[11],
+            content[12],
+        ];
+
+        if width == 0 || height == 0 {
+            return Err(MiniPngError::InvalidPng(
+                "image dimensions must not be zero",
+            ));
+        }
+        if compression != 0 || filter != 0 || interlace > 1 {
+            return Err(MiniPngError::InvalidPng(
+                "unknown compression, filter or interlace method",
+            ));
+        }
+
+        let pixel_type = match (color_type, bit_depth) {
+            (0, 1) => PixelType::BlackAndWhite,
+            (0, 2 | 4 | 8) => PixelType::GrayLevels,
+            (2, 8) => PixelType::RGB,
+            (3, 1 | 2 | 4 | 8) => PixelType::Palette,
+            (0 | 2, 16) => return Err(MiniPngError::UnsupportedPng("16 bit samples")),
+            (4 | 6, _) => return Err(MiniPngError::UnsupportedPng("alpha channel")),
+            _ => return Err(MiniPngError::InvalidPng("invalid bit depth and color type")),
+        };
+        if interlace == 1 {
+            return Err(MiniPngError::UnsupportedPng("interlacing"));
+        }
+
+        Ok(Ihdr {
+            header: Header {
+                width,
+                height,
+                pixel_type,
+            },
+            bit_depth,
+            color_type,
+        })
+    }
+}
+
+// splits the first chunk of `bytes`, returning its type, content and the
+// bytes after it
+fn read_chunk(bytes: &[u8]) -> Result<([u8; 4], &[u8], &[u8])> {
+    let truncated = MiniPngError::InvalidPng("truncated chunk");
+    let (length, rest) = bytes.split_first_chunk::<4>().ok_or(truncated)?;
+    let length = u32::from_be_bytes(*length) as usize;
+    if length > i32::MAX as usize {
+        return Err(MiniPngError::InvalidPng(
+            "chunk length larger than 2^31 - 1",
+        ));
+    }
+
+    let body = rest
+        .get(..4 + length)
+        .ok_or(MiniPngError::InvalidPng("truncated chunk"))?;
+    let crc = rest
+        .get(4 + length..8 + length)
+        .ok_or(MiniPngError::InvalidPng("truncated chunk"))?;
+    let chunk_type: [u8; 4] = [body[0], body[1], body[2], body[3]];
+    if crc32(body).to_be_bytes() != crc {
+        return Err(MiniPngError::PngCrcMismatch(
+            String::from_utf8_lossy(&chunk_type).into_owned(),
+        ));
+    }
+
+    Ok((chunk_type, &body[4..], &rest[8 + length..]))
+}
+
+// turns a text chunk into a comment, the keyword is kept unless it is the
+// `Comment` keyword written by `to_png`
+fn text_chunk(chunk_type: &[u8; 4], content: &[u8], limits: &DecodeLimits) -> Result<String> {
+    let invalid = MiniPngError::InvalidPng("invalid text chunk");
+    let separator = content.iter().position(|&b| b == 0).ok_or(invalid)?;
+    let keyword: String = content[..separator].iter().map(|&b| b as char).collect();
+    let rest = &content[separator + 1..];
+
+    let text = match chunk_type {
+        // Latin-1 text
+        b"tEXt" => rest.iter().map(|&b| b as char).collect(),
+        b"zTXt" => {
+            let compressed = rest
+                .get(1..)
+                .ok_or(MiniPngError::InvalidPng("invalid text chunk"))?;
+            let text = zlib::decompress(compressed, limits.max_comment_length as usize)?;
+            text.iter().map(|&b| b as char).collect()
+        }
+        // UTF-8 text after a compression flag and method, a language tag
+        // and a translated keyword
+        _ => {
+            let [flag, _method, tail @ ..] = rest else {
+                return Err(MiniPngError::InvalidPng("invalid text chunk"));
+            };
+            let mut parts = tail.splitn(3, |&b| b == 0).skip(2);
+            let text = parts
+                .next()
+                .ok_or(MiniPngError::InvalidPng("invalid text chunk"))?;
+            let text = match flag {
+                0 => text.to_vec(),
+                _ => zlib::decompress(text, limits.max_comment_length as usize)?,
+            };
+            String::from_utf8(text).map_err(|_| MiniPngError::InvalidUtf8Comment)?
+        }
+    };
+
+    Ok(match keyword.as_str() {
+        "Comment" => text,
+        _ => format!("{}: {}", keyword, text),
+    })
+}
+
+// reverses the filter of each scanline, `bpp` is the number of bytes per
+// complete pixel, rounded up to 1
+fn unfilter(scanlines: &[u8], row_size: usize, bpp: usize) -> Result<Vec<u8>> {
+    let mut rows = Vec::with_capacity(scanlines.len());
+    let mut previous = vec![0u8; row_size];
+
+    for scanline in scanlines.chunks(row_size + 1) {
+        let (&filter, line) = scanline
+            .split_first()
+            .ok_or(MiniPngError::InvalidPng("empty scanline"))?;
+        let mut row = line.to_vec();
+
+        for i in 0..row.len() {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+
+            row[i] = row[i].wrapping_add(match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(MiniPngError::InvalidPng("unknown scanline filter")),
+            });
+        }
+
+        rows.extend_from_slice(&row);
+        previous = row;
+    }
+
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, chunk_type: &[u8; 4], content: &[u8]) -> Result<()> {
+    let length =
+        u32::try_from(content.len()).map_err(|_| MiniPngError::BlockTooLarge(content.len()))?;
+
+    let mut crc_input = Vec::with_capacity(4 + content.len());
+    crc_input.extend(chunk_type);
+    crc_input.extend(content);
+
+    writer
+        .write_all(&length.to_be_bytes())
+        .map_err(MiniPngError::Io)?;
+    writer.write_all(&crc_input).map_err(MiniPngError::Io)?;
+    writer
+        .write_all(&crc32(&crc_input).to_be_bytes())
+        .map_err(MiniPngError::Io)
+}
@@ -0,0 +1,284 @@
+#[cfg(test)]
+mod tests {
+    use crate::png::crc32;
+    use crate::test_utils::sample;
+    use crate::zlib::compress;
+    use crate::{DecodeLimits, Header, MiniPNG, MiniPngError, Pixel, PixelType};
+
+    // splits a PNG file into (type, content) chunks, checking their CRC
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let mut chunks = Vec::new();
+        let mut pos = 8;
+        while pos < png.len() {
+            let length = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let body = &png[pos + 4..pos + 8 + length];
+            let crc =
+                u32::from_be_bytes(png[pos + 8 + length..pos + 12 + length].try_into().unwrap());
+            assert_eq!(crc32(body), crc);
+            chunks.push((
+                String::from_utf8(body[..4].to_vec()).unwrap(),
+                body[4..].to_vec(),
+            ));
+            pos += 12 + length;
+        }
+        chunks
+    }
+
+    fn chunk(chunk_type: &[u8; 4], content: &[u8]) -> Vec<u8> {
+        let body = [chunk_type.as_slice(), content].concat();
+        [
+            (content.len() as u32).to_be_bytes().as_slice(),
+            &body,
+            &crc32(&body).to_be_bytes(),
+        ]
+        .concat()
+    }
+
+    // a PNG file made of the given IHDR fields and filtered scanlines
+    fn png(width: u32, height: u32, depth: u8, color_type: u8, scanlines: &[u8]) -> Vec<u8> {
+        let ihdr = [
+            width.to_be_bytes().as_slice(),
+            &height.to_be_bytes(),
+            &[depth, color_type, 0, 0, 0],
+        ]
+        .concat();
+        [
+            b"\x89PNG\r\n\x1a\n".to_vec(),
+            chunk(b"IHDR", &ihdr),
+            chunk(b"IDAT", &compress(scanlines)),
+            chunk(b"IEND", &[]),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"IEND"), 0xae426082);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn test_png_chunks() {
+        let mut image = sample(include_bytes!(
+            "../minipng-samples/other/ok/german-flag-palette.mp"
+        ));
+        image.comments = vec!["hello".to_string(), "héllo wörld €".to_string()];
+        let png = image.to_png(Vec::new()).unwrap();
+        let chunks = chunks(&png);
+
+        let types: Vec<&str> = chunks.iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(types, ["IHDR", "tEXt", "iTXt", "PLTE", "IDAT", "IEND"]);
+
+        let ihdr = &chunks[0].1;
+        assert_eq!(ihdr[..4], image.header.width.to_be_bytes());
+        assert_eq!(ihdr[4..8], image.header.height.to_be_bytes());
+        assert_eq!(ihdr[8..], [8, 3, 0, 0, 0]);
+
+        assert_eq!(chunks[1].1, b"Comment\0hello");
+        assert_eq!(chunks[3].1, image.palette.unwrap().colors.as_flattened());
+    }
+
+    #[test]
+    fn test_png_bit_depth_and_color_type() {
+        for (bytes, expected) in [
+            (
+                include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+                [1, 0],
+            ),
+            (
+                include_bytes!("../minipng-samples/other/ok/gray.mp"),
+                [8, 0],
+            ),
+            (
+                include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+                [8, 2],
+            ),
+        ] {
+            let png = sample(bytes).to_png(Vec::new()).unwrap();
+            assert_eq!(chunks(&png)[0].1[8..10], expected);
+        }
+    }
+
+    #[test]
+    fn test_png_rejects_unrepresentable_images() {
+        let empty = MiniPNG::new(
+            Header {
+                width: 0,
+                height: 3,
+                pixel_type: PixelType::GrayLevels,
+            },
+            None,
+        )
+        .unwrap();
+        assert!(matches!(
+            empty.to_png(Vec::new()),
+            Err(MiniPngError::UnsupportedPng(_))
+        ));
+
+        let image = sample(include_bytes!(
+            "../minipng-samples/other/nok/german-flag-error-palette.mp"
+        ));
+        assert!(matches!(
+            image.to_png(Vec::new()),
+            Err(MiniPngError::InvalidPaletteIndex(_))
+        ));
+    }
+
+    #[test]
+    fn test_png_round_trips() {
+        for bytes in [
+            include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+            include_bytes!("../minipng-samples/bw/ok/uneven-dimensions.mp"),
+            include_bytes!("../minipng-samples/other/ok/gray.mp"),
+            include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+            include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp"),
+            include_bytes!("../minipng-samples/other/ok/damier.mp"),
+        ] {
+            let mut image = sample(bytes);
+            image.comments.push("héllo wörld €".to_string());
+
+            let decoded = MiniPNG::from_png(image.to_png(Vec::new()).unwrap().as_slice()).unwrap();
+            assert_eq!(decoded.header, image.header);
+            assert_eq!(decoded.palette, image.palette);
+            assert_eq!(decoded.comments, image.comments);
+            // compares pixels as the padding bits of the samples are not zero
+            assert!(decoded.pixels().unwrap().eq(image.pixels().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_png_scanline_filters() {
+        // 2x5 RGB image, each row uses a different filter and decodes to
+        // [10, 20, 30, 40, 50, 60] plus 10 times the row index
+        let expected: Vec<u8> = (0..5u8)
+            .flat_map(|y| [10, 20, 30, 40, 50, 60].map(|v| v + 10 * y))
+            .collect();
+        let scanlines = [
+            [0, 10, 20, 30, 40, 50, 60],
+            // sub: difference with the pixel on the left
+            [1, 20, 30, 40, 30, 30, 30],
+            // up: difference with the pixel above
+            [2, 10, 10, 10, 10, 10, 10],
+            // average of left and up
+            [3, 25, 30, 35, 20, 20, 20],
+            // paeth predicts from left, up or up left
+            [4, 10, 10, 10, 10, 10, 10],
+        ]
+        .concat();
+
+        let image = MiniPNG::from_png(png(2, 5, 8, 2, &scanlines).as_slice()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::RGB);
+        assert_eq!(image.data, expected);
+    }
+
+    #[test]
+    fn test_png_low_bit_depths() {
+        // 4 bit gray is rescaled to 8 bits
+        let image = MiniPNG::from_png(png(3, 1, 4, 0, &[0, 0x0f, 0x80]).as_slice()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::GrayLevels);
+        assert_eq!(image.data, vec![0, 255, 136]);
+
+        // 1 bit gray is black and white, rows are padded to a byte
+        let image = MiniPNG::from_png(png(3, 2, 1, 0, &[0, 0xa0, 0, 0x40]).as_slice()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::BlackAndWhite);
+        let pixels: Vec<Pixel> = image.pixels().unwrap().collect();
+        assert_eq!(
+            pixels,
+            [true, false, true, false, true, false].map(Pixel::Bw)
+        );
+    }
+
+    #[test]
+    fn test_png_unsupported() {
+        for (depth, color_type, scanlines) in [
+            (16, 0, [0, 0, 0].as_slice()),
+            (8, 6, &[0, 0, 0, 0, 0]),
+            (8, 4, &[0, 0, 0]),
+        ] {
+            let error =
+                MiniPNG::from_png(png(1, 1, depth, color_type, scanlines).as_slice()).unwrap_err();
+            assert!(
+                matches!(error, MiniPngError::UnsupportedPng(_)),
+                "{:?}",
+                error
+            );
+        }
+
+        let mut interlaced = png(1, 1, 8, 0, &[0, 0]);
+        interlaced[28] = 1;
+        let crc = crc32(&interlaced[12..29]).to_be_bytes();
+        interlaced[29..33].copy_from_slice(&crc);
+        assert!(matches!(
+            MiniPNG::from_png(interlaced.as_slice()),
+            Err(MiniPngError::UnsupportedPng("interlacing"))
+        ));
+    }
+
+    #[test]
+    fn test_png_invalid() {
+        let valid = png(2, 1, 8, 0, &[0, 1, 2]);
+        assert!(MiniPNG::from_png(valid.as_slice()).is_ok());
+
+        let mut corrupted = valid.clone();
+        corrupted[20] ^= 1;
+        assert!(matches!(
+            MiniPNG::from_png(corrupted.as_slice()),
+            Err(MiniPngError::PngCrcMismatch(chunk)) if chunk == "IHDR"
+        ));
+
+        assert!(matches!(
+            MiniPNG::from_png(&valid[1..]),
+            Err(MiniPngError::InvalidPng(_))
+        ));
+        assert!(matches!(
+            MiniPNG::from_png(&valid[..valid.len() - 12]),
+            Err(MiniPngError::InvalidPng(_))
+        ));
+        // too few bytes of image data
+        assert!(matches!(
+            MiniPNG::from_png(png(2, 2, 8, 0, &[0, 1, 2]).as_slice()),
+            Err(MiniPngError::InvalidPng(_))
+        ));
+        assert!(matches!(
+            MiniPNG::from_png(png(2, 1, 8, 0, &[5, 1, 2]).as_slice()),
+            Err(MiniPngError::InvalidPng(_))
+        ));
+        // palette images need a PLTE chunk
+        assert!(matches!(
+            MiniPNG::from_png(png(2, 1, 8, 3, &[0, 1, 2]).as_slice()),
+            Err(MiniPngError::MissingPalette)
+        ));
+    }
+
+    #[test]
+    fn test_png_limits() {
+        let limits = DecodeLimits {
+            max_pixels: 2,
+            max_comment_length: 4,
+            ..DecodeLimits::default()
+        };
+        let valid = png(2, 1, 8, 0, &[0, 1, 2]);
+        assert!(MiniPNG::from_png_with_limits(valid.as_slice(), limits.clone()).is_ok());
+
+        let large = png(2, 2, 8, 0, &[0, 1, 2, 0, 3, 4]);
+        assert!(matches!(
+            MiniPNG::from_png_with_limits(large.as_slice(), limits.clone()),
+            Err(MiniPngError::LimitExceeded {
+                limit: "pixel count",
+                ..
+            })
+        ));
+
+        // compressed comments are not inflated past the comment length
+        let text = [b"Comment\0\0".as_slice(), &compress(b"too long")].concat();
+        let end = valid.len() - 12;
+        let commented = [&valid[..end], &chunk(b"zTXt", &text), &valid[end..]].concat();
+        assert!(MiniPNG::from_png(commented.as_slice()).is_ok());
+        assert!(matches!(
+            MiniPNG::from_png_with_limits(commented.as_slice(), limits),
+            Err(MiniPngError::InvalidDeflate("decompressed data too large"))
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::{Read, Write};
+
+use crate::error::{MiniPngError, Result};
+use crate::limits::DecodeLimits;
+use crate::mininpng::{Header, MiniPNG};
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+
+// longest line written in the ASCII formats, as recommended by Netpbm
+const MAX_LINE_LENGTH: usize = 70;
+
+impl MiniPNG {
+    /// Decodes a Netpbm image: PBM (`P1`, `P4`) as black and white, PGM
+    /// (`P2`, `P5`) as gray levels and PPM (`P3`, `P6`) as RGB.
+    ///
+    /// Samples are rescaled from the maxval of the file to `0..=255`, and
+    /// each `#` comment line of the header becomes a comment of the image.
+    /// Only the first image of a multi-image file is read.
+    pub fn from_pnm<R: Read>(reader: R) -> Result<Self> {
+        Self::from_pnm_with_limits(reader, DecodeLimits::default())
+    }
+
+    /// Same as [`MiniPNG::from_pnm`] with custom limits.
+    pub fn from_pnm_with_limits<R: Read>(mut reader: R, limits: DecodeLimits) -> Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).map_err(MiniPngError::Io)?;
+
+        let mut parser = Parser {
+            bytes: &bytes,
+            pos: 0,
+            comments: Vec::new(),
+        };
+
+        let format = match bytes.get(..2) {
+            Some([b'P', format @ b'1'..=b'6']) => format - b'0',
+            _ => return Err(MiniPngError::InvalidPnm("missing P1 to P6 magic number")),
+        };
+        parser.pos = 2;
+
+        let width = parser.number()?;
+        let height = parser.number()?;
+        let maxval = match format {
+            1 | 4 => 1,
+            _ => parser.number()?,
+        };
+        if !(1..=65535).contains(&maxval) {
+            return Err(MiniPngError::InvalidPnm(
+                "maxval must be between 1 and 65535",
+            ));
+        }
+
+        let header = Header {
+            width,
+            height,
+            pixel_type: match format {
+                1 | 4 => PixelType::BlackAndWhite,
+                2 | 5 => PixelType::GrayLevels,
+                _ => PixelType::RGB,
+            },
+        };
+        limits.check_header(&header)?;
+        let mut image = MiniPNG::new(header, None)?;
+
+        if format >= 4 {
+            // a single whitespace separates the header from a binary raster
+            parser.pos += 1;
+        }
+
+        let samples = image.header.pixel_count()? * (if format % 3 == 0 { 3 } else { 1 });
+        match format {
+            1 => {
+                for i in 0..samples {
+                    // 1 is black in PBM
+                    if !parser.bit()? {
+                        image.data[i / 8] |= 1 << (7 - (i % 8));
+                    }
+                }
+            }
+            4 => {
+                let width = width as usize;
+                let row_size = width.div_ceil(8);
+                let raster = parser.raster(row_size * height as usize)?;
+                for i in 0..samples {
+                    let (x, y) = (i % width, i / width);
+                    let bit = (raster[y * row_size + x / 8] >> (7 - (x % 8))) & 1;
+                    if bit == 0 {
+                        image.data[i / 8] |= 1 << (7 - (i % 8));
+                    }
+                }
+            }
+            2 | 3 => {
+                for i in 0..samples {
+                    image.data[i] = rescale(parser.number()?, maxval)?;
+                }
+            }
+            _ => {
+                let sample_size = if maxval < 256 { 1 } else { 2 };
+                let raster = parser.raster(samples * sample_size)?;
+                for (sample, bytes) in image.data.iter_mut().zip(raster.chunks(sample_size)) {
+                    let value = bytes.iter().fold(0, |value, &b| value << 8 | b as u32);
+                    *sample = rescale(value, maxval)?;
+                }
+            }
+        }
+
+        image.comments = parser.comments;
+        Ok(image)
+    }
+
+    /// Encodes the image as Netpbm, in the plain ASCII variant of the format
+    /// when `ascii` is set.
+    ///
+    /// Black and white images are written as PBM, gray levels as PGM, and
+    /// RGB and palette images as PPM. Comments are written as `#` lines.
+    pub fn to_pnm<W: Write>(&self, mut writer: W, ascii: bool) -> Result<W> {
+        let (magic, maxval) = match (self.header.pixel_type, ascii) {
+            (PixelType::BlackAndWhite, true) => ("P1", ""),
+            (PixelType::BlackAndWhite, false) => ("P4", ""),
+            (PixelType::GrayLevels, true) => ("P2", "255\n"),
+            (PixelType::GrayLevels, false) => ("P5", "255\n"),
+            (_, true) => ("P3", "255\n"),
+            (_, false) => ("P6", "255\n"),
+        };
+
+        let mut header = format!("{}\n", magic);
+        for line in self.comments.iter().flat_map(|comment| comment.lines()) {
+            header.push_str(&format!("# {}\n", line));
+        }
+        header.push_str(&format!(
+            "{} {}\n{}",
+            self.header.width, self.header.height, maxval
+        ));
+        writer
+            .write_all(header.as_bytes())
+            .map_err(MiniPngError::Io)?;
+
+        for row in self.rows()? {
+            let mut samples = Vec::new();
+            for pixel in row {
+                match pixel {
+                    Pixel::Bw(white) => samples.push(!white as u8),
+                    Pixel::Gray(value) => samples.push(value),
+                    pixel => samples.extend(self.color_of(pixel)?),
+                }
+            }
+
+            let bytes = match (self.header.pixel_type, ascii) {
+                (_, true) => ascii_row(&samples),
+                (PixelType::BlackAndWhite, false) => {
+                    let mut packed = vec![0u8; samples.len().div_ceil(8)];
+                    for (x, &black) in samples.iter().enumerate() {
+                        packed[x / 8] |= black << (7 - (x % 8));
+                    }
+                    packed
+                }
+                (_, false) => samples,
+            };
+            writer.write_all(&bytes).map_err(MiniPngError::Io)?;
+        }
+
+        writer.flush().map_err(MiniPngError::Io)?;
+        Ok(writer)
+    }
+}
+
+// samples separated by spaces, on lines of at most MAX_LINE_LENGTH characters
+fn ascii_row(samples: &[u8]) -> Vec<u8> {
+    let mut output = String::new();
+    let mut line_length = 0;
+
+    for sample in samples {
+        let text = sample.to_string();
+        if line_length > 0 && line_length + 1 + text.len() > MAX_LINE_LENGTH {
+            output.push('\n');
+            line_length = 0;
+        } else if line_length > 0 {
+            output.push(' ');
+            line_length += 1;
+        }
+        output.push_str(&text);
+        line_length += text.len();
+    }
+    output.push('\n');
+
+    output.into_bytes()
+}
+
+fn rescale(value: u32, maxval: u32) -> Result<u8> {
+    if value > maxval {
+        return Err(MiniPngError::InvalidPnm("sample larger than maxval"));
+    }
+    Ok(((value * 255 + maxval / 2) / maxval) as u8)
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    comments: Vec<String>,
+}
+
+impl Parser<'_> {
+    // skips whitespace and comments, collecting the comments
+    fn skip_separators(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            if byte == b'#' {
+                let start = self.pos + 1;
+                let end = self.bytes[start..]
+                    .iter()
+                    .position(|&b| b == b'\n' || b == b'\r')
+                    .map_or(self.bytes.len(), |length| start + length);
+                let comment = String::from_utf8_lossy(&self.bytes[start..end]);
+                self.comments
+                    .push(comment.strip_prefix(' ').unwrap_or(&comment).to_string());
+                self.pos = end;
+            } else if byte.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<u32> {
+        self.skip_separators();
+        let digits = self.bytes[self.pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(MiniPngError::InvalidPnm("expected a decimal number"));
+        }
+
+        let text = std::str::from_utf8(&self.bytes[self.pos..self.pos + digits])
+            .map_err(|_| MiniPngError::InvalidPnm("expected a decimal number"))?;
+        self.pos += digits;
+        text.parse()
+            .map_err(|_| MiniPngError::InvalidPnm("number too large"))
+    }
+
+    // a single `0` or `1` of a plain PBM raster, which may not be separated
+    // from the next one
+    fn bit(&mut self) -> Result<bool> {
+        self.skip_separators();
+        match self.bytes.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Err(MiniPngError::InvalidPnm("expected 0 or 1 in PBM raster")),
+            None => Err(MiniPngError::InvalidPnm("truncated raster")),
+        }
+    }
+
+    fn raster(&mut self, length: usize) -> Result<&[u8]> {
+        let raster = self
+            .bytes
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..length))
+            .ok_or(MiniPngError::InvalidPnm("truncated raster"))?;
+        self.pos += length;
+        Ok(raster)
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::sample;
+    use crate::{DecodeLimits, MiniPNG, MiniPngError, Pixel, PixelType};
+
+    fn pnm(image: &MiniPNG, ascii: bool) -> Vec<u8> {
+        image.to_pnm(Vec::new(), ascii).unwrap()
+    }
+
+    #[test]
+    fn test_pnm_round_trips() {
+        for bytes in [
+            include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+            include_bytes!("../minipng-samples/bw/ok/uneven-dimensions.mp"),
+            include_bytes!("../minipng-samples/other/ok/gray.mp"),
+            include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+        ] {
+            let image = sample(bytes);
+            for ascii in [true, false] {
+                let decoded = MiniPNG::from_pnm(pnm(&image, ascii).as_slice()).unwrap();
+                assert_eq!(decoded.header, image.header);
+                // compares pixels as the padding bits of the samples are not zero
+                assert!(decoded.pixels().unwrap().eq(image.pixels().unwrap()));
+                assert_eq!(decoded.comments, image.comments);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pnm_palette_is_written_as_rgb() {
+        let image = sample(include_bytes!(
+            "../minipng-samples/other/ok/german-flag-palette.mp"
+        ));
+        let decoded = MiniPNG::from_pnm(pnm(&image, false).as_slice()).unwrap();
+        assert_eq!(decoded.header.pixel_type, PixelType::RGB);
+
+        let colors: Vec<[u8; 3]> = image
+            .pixels()
+            .unwrap()
+            .map(|pixel| image.color_of(pixel).unwrap())
+            .collect();
+        let decoded_colors: Vec<[u8; 3]> = decoded
+            .pixels()
+            .unwrap()
+            .map(|pixel| decoded.color_of(pixel).unwrap())
+            .collect();
+        assert_eq!(decoded_colors, colors);
+    }
+
+    #[test]
+    fn test_plain_pbm_with_comments() {
+        let text = b"P1\n# first comment\n#second\n3 2\n1 0 1\n010";
+        let image = MiniPNG::from_pnm(text.as_slice()).unwrap();
+
+        assert_eq!(image.header.pixel_type, PixelType::BlackAndWhite);
+        assert_eq!(image.comments, vec!["first comment", "second"]);
+        // 1 is black in PBM but white in Mini-PNG
+        assert_eq!(image.get_pixel(0, 0).unwrap(), Pixel::Bw(false));
+        assert_eq!(image.get_pixel(1, 0).unwrap(), Pixel::Bw(true));
+        assert_eq!(image.get_pixel(1, 1).unwrap(), Pixel::Bw(false));
+
+        assert_eq!(
+            pnm(&image, true),
+            b"P1\n# first comment\n# second\n3 2\n1 0 1\n0 1 0\n"
+        );
+    }
+
+    #[test]
+    fn test_binary_pbm_rows_are_padded() {
+        let bytes = b"P4\n10 2\n\xff\xc0\x00\x00";
+        let image = MiniPNG::from_pnm(bytes.as_slice()).unwrap();
+        assert!(
+            image
+                .pixels()
+                .unwrap()
+                .take(10)
+                .all(|p| p == Pixel::Bw(false))
+        );
+        assert!(
+            image
+                .pixels()
+                .unwrap()
+                .skip(10)
+                .all(|p| p == Pixel::Bw(true))
+        );
+        assert_eq!(pnm(&image, false), bytes);
+    }
+
+    #[test]
+    fn test_maxval_rescaling() {
+        let image = MiniPNG::from_pnm(b"P2 3 1 15 0 7 15".as_slice()).unwrap();
+        assert_eq!(image.data, vec![0, 119, 255]);
+
+        let image = MiniPNG::from_pnm(b"P5 2 1 65535\n\x00\x00\xff\xff".as_slice()).unwrap();
+        assert_eq!(image.data, vec![0, 255]);
+
+        let image = MiniPNG::from_pnm(b"P6 1 1 1000\n\x01\xf4\x00\x00\x03\xe8".as_slice()).unwrap();
+        assert_eq!(image.data, vec![128, 0, 255]);
+    }
+
+    #[test]
+    fn test_ascii_lines_are_wrapped() {
+        let text = format!("P2 30 1 255 {}", vec!["200"; 30].join(" "));
+        let image = MiniPNG::from_pnm(text.as_bytes()).unwrap();
+        let output = String::from_utf8(pnm(&image, true)).unwrap();
+        assert!(output.lines().all(|line| line.len() <= 70));
+        assert_eq!(
+            MiniPNG::from_pnm(output.as_bytes()).unwrap().data,
+            image.data
+        );
+    }
+
+    #[test]
+    fn test_invalid_pnm() {
+        for bytes in [
+            b"P7 1 1 255\n\x00".as_slice(),
+            b"P5 1 1 0\n\x00",
+            b"P5 1 1 70000\n\x00",
+            b"P5 2 2 255\n\x00",
+            b"P2 1 1 15 16",
+            b"P1 2 1 1 2",
+            b"P3 1 x 255",
+        ] {
+            let error = MiniPNG::from_pnm(bytes).unwrap_err();
+            assert!(matches!(error, MiniPngError::InvalidPnm(_)), "{:?}", error);
+        }
+
+        assert!(matches!(
+            MiniPNG::from_pnm(b"P5 100000 1 255\n".as_slice()),
+            Err(MiniPngError::LimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pnm_limits() {
+        let limits = DecodeLimits {
+            max_width: 2,
+            ..DecodeLimits::default()
+        };
+        assert!(
+            MiniPNG::from_pnm_with_limits(b"P2 2 1 255 0 255".as_slice(), limits.clone()).is_ok()
+        );
+        assert!(matches!(
+            MiniPNG::from_pnm_with_limits(b"P2 3 1 255 0 0 0".as_slice(), limits),
+            Err(MiniPngError::LimitExceeded {
+                limit: "width",
+                value: 3,
+                max: 2
+            })
+        ));
+    }
+}
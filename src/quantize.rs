@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use crate::mininpng::Palette;
+
+/// How [`MiniPNG::convert`](crate::MiniPNG::convert) builds a palette from
+/// the colors of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Quantizer {
+    /// Keeps every color, fails if there are too many of them.
+    #[default]
+    Exact,
+    /// Recursively splits the color space at the median of its widest channel.
+    MedianCut,
+    /// Merges the leaves of an octree of the colors until few enough remain.
+    Octree,
+}
+
+impl Palette {
+    /// Index of the closest palette color, by euclidean distance in RGB.
+    ///
+    /// Only the first 256 colors can be addressed by a pixel, the others are
+    /// ignored. Returns `None` if the palette is empty.
+    pub fn nearest(&self, [r, g, b]: [u8; 3]) -> Option<u8> {
+        self.colors
+            .iter()
+            .take(256)
+            .enumerate()
+            .min_by_key(|(_, [pr, pg, pb])| {
+                let dr = r as i32 - *pr as i32;
+                let dg = g as i32 - *pg as i32;
+                let db = b as i32 - *pb as i32;
+                dr * dr + dg * dg + db * db
+            })
+            .map(|(index, _)| index as u8)
+    }
+}
+
+// number of pixels of each distinct color
+fn histogram(colors: &[[u8; 3]]) -> Vec<([u8; 3], u64)> {
+    let mut counts: HashMap<[u8; 3], u64> = HashMap::new();
+    for &color in colors {
+        *counts.entry(color).or_default() += 1;
+    }
+
+    let mut histogram: Vec<_> = counts.into_iter().collect();
+    // hash map order is random, sorting keeps the palettes reproducible
+    histogram.sort_unstable();
+    histogram
+}
+
+fn accumulate(sum: &mut [u64; 3], color: [u8; 3], count: u64) {
+    for (total, channel) in sum.iter_mut().zip(color) {
+        *total += channel as u64 * count;
+    }
+}
+
+fn average(sum: [u64; 3], count: u64) -> [u8; 3] {
+    let count = count.max(1);
+    sum.map(|channel| ((channel + count / 2) / count) as u8)
+}
+
+/// Builds a palette of at most `max_colors` colors with the median cut algorithm.
+///
+/// Each box of colors is split in two at the pixel weighted median of its
+/// widest channel, until there are `max_colors` boxes or every box holds a
+/// single color. Each palette entry is the weighted average of a box.
+pub fn median_cut(colors: &[[u8; 3]], max_colors: usize) -> Palette {
+    let max_colors = max_colors.clamp(1, 256);
+    let mut boxes = vec![histogram(colors)];
+    boxes.retain(|colors| !colors.is_empty());
+
+    while boxes.len() < max_colors {
+        // the box spanning the widest range along a single channel is split first
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, colors)| colors.len() > 1)
+            .map(|(i, colors)| {
+                let (channel, range) = widest_channel(colors);
+                (range, i, channel)
+            })
+            .max();
+        let Some((_, i, channel)) = widest else {
+            break;
+        };
+
+        let mut colors = boxes.swap_remove(i);
+        colors.sort_unstable_by_key(|(color, _)| color[channel]);
+
+        let total: u64 = colors.iter().map(|(_, count)| count).sum();
+        let mut seen = 0;
+        let mut split = colors.len() - 1;
+        for (j, (_, count)) in colors.iter().enumerate() {
+            seen += count;
+            if seen * 2 >= total {
+                split = j + 1;
+                break;
+            }
+        }
+        // both halves must hold at least one color
+        let split = split.clamp(1, colors.len() - 1);
+
+        let upper = colors.split_off(split);
+        boxes.push(colors);
+        boxes.push(upper);
+    }
+
+    Palette {
+        colors: boxes
+            .iter()
+            .map(|colors| {
+                let mut sum = [0u64; 3];
+                let mut count = 0;
+                for &(color, n) in colors {
+                    accumulate(&mut sum, color, n);
+                    count += n;
+                }
+                average(sum, count)
+            })
+            .collect(),
+    }
+}
+
+fn widest_channel(colors: &[([u8; 3], u64)]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let min = colors.iter().map(|(c, _)| c[channel]).min().unwrap_or(0);
+            let max = colors.iter().map(|(c, _)| c[channel]).max().unwrap_or(0);
+            (channel, max - min)
+        })
+        .max_by_key(|&(channel, range)| (range, std::cmp::Reverse(channel)))
+        .unwrap_or((0, 0))
+}
+
+#[derive(Default)]
+struct OctreeNode {
+    sum: [u64; 3],
+    count: u64,
+    children: [Option<usize>; 8],
+    is_leaf: bool,
+}
+
+/// Builds a palette of at most `max_colors` colors with an octree.
+///
+/// Colors are inserted in a tree whose level `n` splits each channel on its
+/// `n`-th most significant bit. Whenever there are more than `max_colors`
+/// leaves, the deepest node is merged with its children.
+pub fn octree(colors: &[[u8; 3]], max_colors: usize) -> Palette {
+    let max_colors = max_colors.clamp(1, 256);
+    let mut nodes = vec![OctreeNode::default()];
+    // non-leaf nodes of each level, the candidates for a merge
+    let mut reducible: [Vec<usize>; 8] = Default::default();
+    reducible[0].push(0);
+    let mut leaves = 0;
+
+    for (color, count) in histogram(colors) {
+        let mut node = 0;
+        for level in 0..8 {
+            if nodes[node].is_leaf {
+                break;
+            }
+
+            let bit = 7 - level;
+            let child = (((color[0] >> bit) & 1) << 2
+                | ((color[1] >> bit) & 1) << 1
+                | ((color[2] >> bit) & 1)) as usize;
+
+            node = match nodes[node].children[child] {
+                Some(existing) => existing,
+                None => {
+                    let created = nodes.len();
+                    let is_leaf = level == 7;
+                    nodes.push(OctreeNode {
+                        is_leaf,
+                        ..OctreeNode::default()
+                    });
+                    nodes[node].children[child] = Some(created);
+
+                    if is_leaf {
+                        leaves += 1;
+                    } else {
+                        reducible[level + 1].push(created);
+                    }
+                    created
+                }
+            };
+        }
+
+        accumulate(&mut nodes[node].sum, color, count);
+        nodes[node].count += count;
+
+        while leaves > max_colors {
+            let Some(level) = (0..8).rev().find(|&level| !reducible[level].is_empty()) else {
+                break;
+            };
+            let Some(merged) = reducible[level].pop() else {
+                break;
+            };
+
+            let mut children = 0;
+            for child in nodes[merged].children.into_iter().flatten() {
+                let (sum, count) = (nodes[child].sum, nodes[child].count);
+                for (total, channel) in nodes[merged].sum.iter_mut().zip(sum) {
+                    *total += channel;
+                }
+                nodes[merged].count += count;
+                children += 1;
+            }
+            nodes[merged].children = [None; 8];
+            nodes[merged].is_leaf = true;
+            leaves = leaves + 1 - children;
+        }
+    }
+
+    let mut palette = Palette { colors: Vec::new() };
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        let node = &nodes[node];
+        if node.is_leaf {
+            palette.colors.push(average(node.sum, node.count));
+        } else {
+            stack.extend(node.children.iter().rev().flatten());
+        }
+    }
+    palette
+}
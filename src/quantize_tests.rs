@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConvertOptions, Header, MiniPNG, Palette, Pixel, PixelType, Quantizer, median_cut, octree,
+    };
+
+    // 16x16 gradient with 256 distinct colors plus a few outliers
+    fn gradient() -> Vec<[u8; 3]> {
+        let mut colors: Vec<[u8; 3]> = (0..=255u8).map(|i| [i, 255 - i, i / 2]).collect();
+        colors.extend_from_slice(&[[255, 0, 0], [0, 255, 0], [0, 0, 255]]);
+        colors
+    }
+
+    #[test]
+    fn test_nearest() {
+        let palette = Palette {
+            colors: vec![[0, 0, 0], [255, 255, 255], [255, 0, 0]],
+        };
+        assert_eq!(palette.nearest([10, 20, 5]), Some(0));
+        assert_eq!(palette.nearest([200, 180, 220]), Some(1));
+        assert_eq!(palette.nearest([180, 40, 30]), Some(2));
+        assert_eq!(Palette { colors: vec![] }.nearest([0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_median_cut_respects_max_colors() {
+        let colors = gradient();
+        for max_colors in [1, 2, 7, 16, 256] {
+            let palette = median_cut(&colors, max_colors);
+            assert!(palette.colors.len() <= max_colors);
+            assert!(!palette.colors.is_empty());
+        }
+
+        // two clusters are found as two colors
+        let clusters = [[10, 10, 10], [12, 12, 12], [240, 240, 240], [250, 250, 250]];
+        let palette = median_cut(&clusters, 2);
+        assert_eq!(palette.colors, vec![[11, 11, 11], [245, 245, 245]]);
+    }
+
+    #[test]
+    fn test_octree_respects_max_colors() {
+        let colors = gradient();
+        for max_colors in [1, 2, 7, 16, 256] {
+            let palette = octree(&colors, max_colors);
+            assert!(palette.colors.len() <= max_colors);
+            assert!(!palette.colors.is_empty());
+        }
+
+        let palette = octree(&[[0, 0, 0], [0, 0, 0], [255, 255, 255]], 1);
+        assert_eq!(palette.colors, vec![[85, 85, 85]]);
+    }
+
+    #[test]
+    fn test_convert_quantized_palette() {
+        let header = Header {
+            width: 16,
+            height: 17,
+            pixel_type: PixelType::RGB,
+        };
+        let mut image = MiniPNG::new(header, None).unwrap();
+        for (i, color) in gradient().into_iter().enumerate() {
+            image
+                .set_pixel(i as u32 % 16, i as u32 / 16, Pixel::Rgb(color))
+                .unwrap();
+        }
+
+        assert!(
+            image
+                .convert(PixelType::Palette, &ConvertOptions::default())
+                .is_err()
+        );
+
+        for quantizer in [Quantizer::MedianCut, Quantizer::Octree] {
+            let options = ConvertOptions {
+                quantizer,
+                max_colors: 32,
+                ..ConvertOptions::default()
+            };
+            let indexed = image.convert(PixelType::Palette, &options).unwrap();
+            let palette = indexed.palette.as_ref().unwrap();
+
+            assert!(palette.colors.len() <= 32);
+            assert!(
+                indexed
+                    .data
+                    .iter()
+                    .all(|&i| (i as usize) < palette.colors.len())
+            );
+            assert_eq!(
+                indexed.get_pixel(0, 16).unwrap(),
+                Pixel::Index(palette.nearest([255, 0, 0]).unwrap())
+            );
+
+            let bytes = indexed.write_to(Vec::new()).unwrap();
+            assert!(MiniPNG::read_from(bytes.as_slice()).is_ok());
+        }
+    }
+}
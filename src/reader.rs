@@ -0,0 +1,357 @@
+use std::io::{self, Read};
+
+use crate::error::{MiniPngError, Result};
+use crate::limits::{BlockBudget, DecodeLimits};
+use crate::mininpng::{Header, MiniPNG, Palette};
+use crate::options::{DecodeOptions, Strictness, Warning};
+use crate::pixel_type::PixelType;
+
+/// A single block read from a Mini-PNG stream.
+#[derive(Debug, Clone)]
+pub enum Block {
+    /// An `H` block.
+    Header(Header),
+    /// A `P` block.
+    Palette(Palette),
+    /// A `C` block.
+    Comment(String),
+    /// A `D` block.
+    Data(Vec<u8>),
+}
+
+/// Streaming Mini-PNG decoder.
+///
+/// The magic number is checked by [`MiniPngReader::new`], then blocks are
+/// read one at a time with [`MiniPngReader::next_block`] or by iterating over
+/// the reader. Only the block being read is held in memory, and its size is
+/// bounded by the [`DecodeLimits`] of the reader.
+///
+/// In [`Strictness::Lenient`] mode, recoverable problems don't stop the
+/// decoding and are collected in [`MiniPngReader::warnings`] instead.
+pub struct MiniPngReader<R: Read> {
+    inner: R,
+    budget: BlockBudget,
+    strictness: Strictness,
+    warnings: Vec<Warning>,
+    has_header: bool,
+    has_palette: bool,
+    done: bool,
+    // location of the next block, reported in parse errors
+    offset: u64,
+    block_index: usize,
+}
+
+impl<R: Read> MiniPngReader<R> {
+    /// Reads and checks the "Mini-PNG" magic number from `inner`, with the
+    /// default [`DecodeOptions`].
+    pub fn new(inner: R) -> Result<Self> {
+        Self::with_options(inner, DecodeOptions::default())
+    }
+
+    /// Same as [`MiniPngReader::new`] with custom limits.
+    pub fn with_limits(inner: R, limits: DecodeLimits) -> Result<Self> {
+        Self::with_options(
+            inner,
+            DecodeOptions {
+                limits,
+                ..DecodeOptions::default()
+            },
+        )
+    }
+
+    /// Same as [`MiniPngReader::new`] with custom options.
+    pub fn with_options(mut inner: R, options: DecodeOptions) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        read_exact_or(&mut inner, &mut magic, MiniPngError::FileTooSmall)?;
+        if &magic != b"Mini-PNG" {
+            return Err(MiniPngError::InvalidMagicNumber);
+        }
+
+        Ok(MiniPngReader {
+            inner,
+            budget: BlockBudget::new(options.limits),
+            strictness: options.strictness,
+            warnings: Vec::new(),
+            has_header: false,
+            has_palette: false,
+            done: false,
+            offset: 8,
+            block_index: 0,
+        })
+    }
+
+    /// Problems worked around so far in lenient mode.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Reads the next block, or returns `None` at the end of the stream.
+    ///
+    /// Errors raised while reading a block are wrapped in
+    /// [`MiniPngError::InBlock`] with the index and offset of the block.
+    pub fn next_block(&mut self) -> Result<Option<Block>> {
+        loop {
+            let Some(block_type) = self.read_block_type()? else {
+                return Ok(None);
+            };
+
+            let (index, offset) = (self.block_index, self.offset);
+            let block = self
+                .read_block(block_type)
+                .map_err(|e| e.in_block(index, offset, block_type))?;
+            self.block_index += 1;
+
+            // `None` is a block skipped in lenient mode
+            if block.is_some() {
+                return Ok(block);
+            }
+        }
+    }
+
+    fn read_block(&mut self, block_type: u8) -> Result<Option<Block>> {
+        let (index, offset) = (self.block_index, self.offset);
+        let lenient = self.strictness == Strictness::Lenient;
+
+        let mut length = [0u8; 4];
+        match read_exact_or(
+            &mut self.inner,
+            &mut length,
+            MiniPngError::InvalidBlockLength,
+        ) {
+            Err(MiniPngError::InvalidBlockLength) if lenient => {
+                self.warnings.push(Warning::TruncatedLength {
+                    index,
+                    offset,
+                    block_type,
+                });
+                return Ok(None);
+            }
+            result => result?,
+        }
+        let length = u32::from_be_bytes(length);
+        self.budget.spend(block_type, length)?;
+
+        // the content is read through `take` so that a forged length does not
+        // allocate more than what the stream actually holds
+        let mut content = Vec::new();
+        (&mut self.inner)
+            .take(length as u64)
+            .read_to_end(&mut content)
+            .map_err(MiniPngError::Io)?;
+
+        if content.len() != length as usize {
+            if !lenient {
+                return Err(MiniPngError::BlockLengthMismatch {
+                    declared: length,
+                    remaining: content.len(),
+                });
+            }
+
+            self.warnings.push(Warning::TruncatedBlock {
+                index,
+                offset,
+                block_type,
+                declared: length,
+                remaining: content.len(),
+            });
+            // the stream is over, only the pixels are worth keeping
+            if block_type != b'D' {
+                return Ok(None);
+            }
+        }
+        self.offset += 5 + length as u64;
+
+        let block = match block_type {
+            b'C' => {
+                // this allows for utf8 encoded comments which is not explicitly supported by
+                // the spec which refers to ascii encoded comments
+                let text = match String::from_utf8(content) {
+                    Ok(text) => text,
+                    Err(e) if lenient => {
+                        self.warnings
+                            .push(Warning::InvalidUtf8Comment { index, offset });
+                        String::from_utf8_lossy(e.as_bytes()).into_owned()
+                    }
+                    Err(_) => return Err(MiniPngError::InvalidUtf8Comment),
+                };
+                Block::Comment(text)
+            }
+
+            b'D' => Block::Data(content),
+
+            b'H' => {
+                if self.has_header {
+                    if lenient {
+                        self.warnings
+                            .push(Warning::DuplicateHeader { index, offset });
+                        return Ok(None);
+                    }
+                    return Err(MiniPngError::DuplicateHeader);
+                }
+                let header = Header::parse(&content)?;
+                self.budget.limits.check_header(&header)?;
+                self.has_header = true;
+
+                Block::Header(header)
+            }
+
+            b'P' => {
+                if self.has_palette {
+                    if lenient {
+                        self.warnings
+                            .push(Warning::DuplicatePalette { index, offset });
+                        return Ok(None);
+                    }
+                    return Err(MiniPngError::DuplicatePalette);
+                }
+                self.has_palette = true;
+
+                Block::Palette(Palette::parse(&content))
+            }
+
+            _ if lenient => {
+                self.warnings.push(Warning::UnknownBlock {
+                    index,
+                    offset,
+                    block_type,
+                });
+                return Ok(None);
+            }
+
+            _ => return Err(MiniPngError::InvalidBlockType(block_type as char)),
+        };
+
+        Ok(Some(block))
+    }
+
+    /// Reads every remaining block and assembles them into a [`MiniPNG`].
+    pub fn read_image(self) -> Result<MiniPNG> {
+        self.read_image_with_warnings().map(|(image, _)| image)
+    }
+
+    /// Same as [`MiniPngReader::read_image`], also returning the warnings
+    /// raised in lenient mode.
+    pub fn read_image_with_warnings(mut self) -> Result<(MiniPNG, Vec<Warning>)> {
+        let mut header: Option<Header> = None;
+        let mut palette: Option<Palette> = None;
+        let mut data: Vec<u8> = Vec::new();
+        let mut comments: Vec<String> = Vec::new();
+
+        while let Some(block) = self.next_block()? {
+            match block {
+                Block::Header(h) => header = Some(h),
+                Block::Palette(p) => palette = Some(p),
+                Block::Comment(text) => comments.push(text),
+                Block::Data(content) => data.extend(content),
+            }
+        }
+
+        if self.strictness == Strictness::Lenient {
+            let header = header.ok_or(MiniPngError::MissingHeader)?;
+            let image = repair(
+                header,
+                palette,
+                data,
+                comments,
+                &mut self.budget,
+                &mut self.warnings,
+            )?;
+            return Ok((image, self.warnings));
+        }
+
+        let image = MiniPNG::from_parts(header, palette, data, comments)?;
+        Ok((image, self.warnings))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_block_type(&mut self) -> Result<Option<u8>> {
+        let mut block_type = [0u8; 1];
+        loop {
+            match self.inner.read(&mut block_type) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(block_type[0])),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(MiniPngError::Io(e)),
+            }
+        }
+    }
+}
+
+// lenient counterpart of `MiniPNG::from_parts`, turns every problem that
+// still leaves a displayable image into a warning
+fn repair(
+    header: Header,
+    mut palette: Option<Palette>,
+    mut data: Vec<u8>,
+    comments: Vec<String>,
+    budget: &mut BlockBudget,
+    warnings: &mut Vec<Warning>,
+) -> Result<MiniPNG> {
+    match header.pixel_type {
+        PixelType::Palette if palette.is_none() => return Err(MiniPngError::MissingPalette),
+        PixelType::Palette => {}
+        _ if palette.is_some() => {
+            warnings.push(Warning::IgnoredPalette);
+            palette = None;
+        }
+        _ => {}
+    }
+
+    let expected = header.data_size()?;
+    if data.len() < expected {
+        budget.pad(expected - data.len())?;
+        warnings.push(Warning::PaddedData {
+            missing: expected - data.len(),
+        });
+    } else if data.len() > expected {
+        warnings.push(Warning::TruncatedData {
+            extra: data.len() - expected,
+        });
+    }
+    data.resize(expected, 0);
+
+    if let Some(palette) = &mut palette {
+        let needed = data.iter().max().map_or(0, |&max| max as usize + 1);
+        if needed > palette.colors.len() {
+            warnings.push(Warning::PaddedPalette {
+                added: needed - palette.colors.len(),
+            });
+            palette.colors.resize(needed, [0, 0, 0]);
+        }
+    }
+
+    Ok(MiniPNG {
+        header,
+        palette,
+        data,
+        comments,
+    })
+}
+
+impl<R: Read> Iterator for MiniPngReader<R> {
+    type Item = Result<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let block = self.next_block().transpose();
+        if !matches!(block, Some(Ok(_))) {
+            self.done = true;
+        }
+        block
+    }
+}
+
+// same as `read_exact` but a truncated stream is reported as `eof_error`
+fn read_exact_or(reader: &mut impl Read, buf: &mut [u8], eof_error: MiniPngError) -> Result<()> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => eof_error,
+        _ => MiniPngError::Io(e),
+    })
+}
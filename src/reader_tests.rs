@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::{Block, DecodeOptions, MiniPNG, MiniPngError, MiniPngReader, PixelType, Warning};
+
+    // reader that hands out a single byte per call, like a slow socket
+    struct OneByteAtATime<'a>(&'a [u8]);
+
+    impl Read for OneByteAtATime<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((&byte, rest)), Some(slot)) => {
+                    *slot = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_yields_blocks_in_file_order() {
+        let bytes = include_bytes!("../minipng-samples/bw/ok/unordered_A.mp");
+        let blocks: Vec<Block> = MiniPngReader::new(&bytes[..])
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(blocks.len(), 3);
+        assert!(matches!(&blocks[0], Block::Comment(text) if text == "La lettre A"));
+        assert!(matches!(&blocks[1], Block::Data(data) if data.len() == 10));
+        match &blocks[2] {
+            Block::Header(header) => {
+                assert_eq!(header.width, 8);
+                assert_eq!(header.height, 10);
+                assert_eq!(header.pixel_type, PixelType::BlackAndWhite);
+            }
+            other => panic!("Expected a header block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reader_matches_in_memory_parser() {
+        let bytes = include_bytes!("../minipng-samples/other/ok/french-palette.mp");
+        let streamed = MiniPNG::read_from(OneByteAtATime(bytes)).unwrap();
+        let parsed: MiniPNG = bytes.to_vec().try_into().unwrap();
+
+        assert_eq!(streamed.header, parsed.header);
+        assert_eq!(streamed.palette, parsed.palette);
+        assert_eq!(streamed.data, parsed.data);
+        assert_eq!(streamed.comments, parsed.comments);
+    }
+
+    #[test]
+    fn test_reader_wrong_magic() {
+        let bytes = include_bytes!("../minipng-samples/bw/nok/wrong-magic.mp");
+        assert!(matches!(
+            MiniPngReader::new(&bytes[..]),
+            Err(MiniPngError::InvalidMagicNumber)
+        ));
+        assert!(matches!(
+            MiniPngReader::new(&b"Mini"[..]),
+            Err(MiniPngError::FileTooSmall)
+        ));
+    }
+
+    #[test]
+    fn test_reader_truncated_block() {
+        let mut bytes = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        bytes.truncate(bytes.len() - 3);
+        let mut reader = MiniPngReader::new(bytes.as_slice()).unwrap();
+
+        assert!(matches!(reader.next(), Some(Ok(Block::Header(_)))));
+        assert!(matches!(reader.next(), Some(Ok(Block::Comment(_)))));
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(matches!(
+            error.root_cause(),
+            MiniPngError::BlockLengthMismatch {
+                declared: 10,
+                remaining: 7
+            }
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_reader_errors_carry_block_location() {
+        let mut bytes = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        bytes.truncate(bytes.len() - 3);
+
+        let error = MiniPNG::read_from(bytes.as_slice()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "block #2 ('D') at offset 0x26: declared length 10 but only 7 bytes remain"
+        );
+
+        let mut bytes = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        bytes[22] = b'Z';
+        let error = MiniPNG::read_from(bytes.as_slice()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "block #1 ('Z') at offset 0x16: Invalid block type found: Z"
+        );
+        assert!(matches!(
+            error,
+            MiniPngError::InBlock {
+                index: 1,
+                offset: 22,
+                block_type: b'Z',
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_reader_invalid_files() {
+        let missing_header = include_bytes!("../minipng-samples/bw/nok/missing-header.mp");
+        assert!(matches!(
+            MiniPNG::read_from(&missing_header[..]),
+            Err(MiniPngError::MissingHeader)
+        ));
+
+        let missing_data = include_bytes!("../minipng-samples/bw/nok/missing-data.mp");
+        assert!(matches!(
+            MiniPNG::read_from(&missing_data[..]),
+            Err(MiniPngError::MissingData)
+        ));
+
+        let broken = include_bytes!("../minipng-samples/bw/nok/broken-dimensions.mp");
+        assert!(matches!(
+            MiniPNG::read_from(&broken[..]),
+            Err(MiniPngError::DataSizeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_lenient_recovers_truncated_data() {
+        let mut bytes = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        bytes.truncate(bytes.len() - 3);
+        bytes.splice(22..22, [b'Z', 0, 0, 0, 1, 0xff]);
+
+        assert!(MiniPNG::read_from(bytes.as_slice()).is_err());
+
+        let (image, warnings) =
+            MiniPNG::read_with_options(bytes.as_slice(), DecodeOptions::lenient()).unwrap();
+        assert_eq!(
+            warnings,
+            vec![
+                Warning::UnknownBlock {
+                    index: 1,
+                    offset: 22,
+                    block_type: b'Z'
+                },
+                Warning::TruncatedBlock {
+                    index: 3,
+                    offset: 44,
+                    block_type: b'D',
+                    declared: 10,
+                    remaining: 7
+                },
+                Warning::PaddedData { missing: 3 },
+            ]
+        );
+        assert_eq!(image.comments, vec!["La lettre A".to_string()]);
+        assert_eq!(
+            &image.data[..7],
+            &[0xc3, 0xbd, 0x7e, 0x7e, 0x7e, 0x00, 0x7e]
+        );
+        assert_eq!(&image.data[7..], &[0, 0, 0]);
+        assert!(image.display().is_ok());
+    }
+
+    #[test]
+    fn test_lenient_repairs_palette() {
+        let bytes = include_bytes!("../minipng-samples/other/nok/german-flag-error-palette.mp");
+        let (image, warnings) =
+            MiniPNG::read_with_options(&bytes[..], DecodeOptions::lenient()).unwrap();
+
+        assert_eq!(warnings, vec![Warning::PaddedPalette { added: 1 }]);
+        assert_eq!(image.palette.as_ref().unwrap().colors.len(), 3);
+        assert!(image.display().is_ok());
+
+        let mut bytes = include_bytes!("../minipng-samples/bw/ok/A.mp").to_vec();
+        bytes.extend_from_slice(&[b'P', 0, 0, 0, 3, 1, 2, 3]);
+        let (image, warnings) =
+            MiniPNG::read_with_options(bytes.as_slice(), DecodeOptions::lenient()).unwrap();
+        assert_eq!(warnings, vec![Warning::IgnoredPalette]);
+        assert!(image.palette.is_none());
+    }
+}
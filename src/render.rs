@@ -0,0 +1,242 @@
+use crate::convert::luminance;
+use crate::error::Result;
+use crate::graphics::GraphicsProtocol;
+use crate::mininpng::MiniPNG;
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+use crate::scale::BwScaling;
+use crate::terminal::{ColorSupport, ramp, ramp_char};
+
+// quadrant characters indexed by their lit quarters, upper left first
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+// braille dot of each pixel of a 2x4 cell, row after row
+const BRAILLE_DOTS: [u8; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+/// How pixels are drawn in a terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenderMode {
+    /// Two characters per pixel and one line per row.
+    #[default]
+    Full,
+    /// `▀` with the upper pixel as foreground and the lower one as
+    /// background, packing two rows in one line.
+    HalfBlock,
+    /// Quadrant characters, 2x2 pixels per character.
+    Quadrant,
+    /// Braille patterns, 2x4 pixels per character.
+    Braille,
+}
+
+/// Options of [`MiniPNG::render`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RenderOptions {
+    /// Characters the pixels are drawn with.
+    pub mode: RenderMode,
+    /// Colors used by the full and half-block modes.
+    pub color: ColorSupport,
+    /// Columns and lines the pixels are shrunk to fit in, keeping their
+    /// aspect ratio.
+    pub fit: Option<(u32, u32)>,
+    /// How black and white pixels are merged when the image is shrunk.
+    pub bw_scaling: BwScaling,
+    /// Draws a bitmap with a graphics protocol instead of characters,
+    /// ignoring the other options.
+    pub protocol: Option<GraphicsProtocol>,
+}
+
+impl MiniPNG {
+    /// Draws the pixels of the image with ANSI escape sequences, each row of
+    /// characters starting with a newline.
+    ///
+    /// Colors are reduced to the ones of `options.color`, and replaced by
+    /// characters of a luminance ramp for [`ColorSupport::Monochrome`].
+    /// Images larger than `options.fit` are shrunk with [`MiniPNG::downscale`].
+    ///
+    /// Black and white images are drawn with characters only. The quadrant
+    /// and braille modes only draw lit and unlit dots, other images are
+    /// lit where their [`luminance`] is at least 128.
+    pub fn render(&self, options: &RenderOptions) -> Result<String> {
+        self.header.check_data_size(self.data.len())?;
+
+        if let Some(protocol) = options.protocol {
+            return Ok(format!("\n{}", self.to_graphics(protocol)?));
+        }
+
+        if let Some((columns, lines)) = options.fit
+            && self.header.pixel_count()? > 0
+        {
+            // share of the image that fits, for a given number of pixels per
+            // character
+            let fit = |(x, y): (f64, f64)| {
+                (columns as f64 * x / self.header.width as f64)
+                    .min(lines as f64 * y / self.header.height as f64)
+            };
+            let black_and_white = self.header.pixel_type == PixelType::BlackAndWhite;
+
+            if fit(pixels_per_char(options.mode, black_and_white)) < 1.0 {
+                // coverage turns black and white pixels into gray levels
+                let scale = fit(pixels_per_char(
+                    options.mode,
+                    black_and_white && options.bw_scaling == BwScaling::Majority,
+                ));
+                let width = ((self.header.width as f64 * scale) as u32).max(1);
+                let height = ((self.header.height as f64 * scale) as u32).max(1);
+                return self
+                    .downscale(width, height, options.bw_scaling)?
+                    .render(&RenderOptions {
+                        fit: None,
+                        ..options.clone()
+                    });
+            }
+        }
+
+        match (options.mode, self.header.pixel_type) {
+            (RenderMode::Full, PixelType::BlackAndWhite) => self.display_black_and_white(),
+            (RenderMode::Full, _) => self.display_colors(options.color),
+            (RenderMode::HalfBlock, _) => self.render_half_blocks(options.color),
+            (RenderMode::Quadrant, _) => self.render_cells(2, 2, |bits| QUADRANTS[bits as usize]),
+            (RenderMode::Braille, _) => self.render_cells(2, 4, |bits| {
+                let dots = (0..8)
+                    .filter(|i| bits & (1 << i) != 0)
+                    .fold(0, |dots, i| dots | BRAILLE_DOTS[i]);
+                char::from_u32(0x2800 + dots as u32).unwrap_or(' ')
+            }),
+        }
+    }
+
+    fn display_black_and_white(&self) -> Result<String> {
+        let mut output = String::new();
+        let total_pixels = self.header.pixel_count()?;
+        for i in 0..total_pixels {
+            let byte = self.data[i / 8];
+
+            if i % self.header.width as usize == 0 {
+                output.push('\n');
+            }
+
+            let pixel = (byte >> (7 - (i % 8))) & 1;
+
+            if pixel == 0 {
+                output.push(' '); // black
+            } else {
+                output.push('X'); // white
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn display_colors(&self, color: ColorSupport) -> Result<String> {
+        let ramp = ramp();
+        let mut output = String::new();
+        for row in self.rows()? {
+            output.push('\n');
+            for pixel in row {
+                let pixel_color = self.color_of(pixel)?;
+                match color {
+                    ColorSupport::Monochrome => {
+                        let c = ramp_char(&ramp, pixel_color);
+                        output.extend([c, c]);
+                    }
+                    color => {
+                        output.push_str(&color.escape(pixel_color, false));
+                        output.push_str("██\x1b[0m");
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn render_half_blocks(&self, color: ColorSupport) -> Result<String> {
+        let rows: Vec<Vec<Pixel>> = self.rows()?.collect();
+        let ramp = ramp();
+        let mut output = String::new();
+
+        for pair in rows.chunks(2) {
+            output.push('\n');
+            for (x, &top) in pair[0].iter().enumerate() {
+                // the last line of an odd height only has upper pixels
+                let bottom = pair.get(1).map(|row| row[x]);
+                match (top, bottom) {
+                    (Pixel::Bw(top), bottom) => {
+                        let bottom = bottom == Some(Pixel::Bw(true));
+                        output.push(QUADRANTS[top as usize * 3 + bottom as usize * 12]);
+                    }
+                    // without colors both pixels share one ramp character
+                    (top, bottom) if color == ColorSupport::Monochrome => {
+                        let [top, bottom] = [top, bottom.unwrap_or(top)]
+                            .map(|pixel| self.color_of(pixel).map(luminance));
+                        output.push(ramp_char(
+                            &ramp,
+                            [((top? as u16 + bottom? as u16) / 2) as u8; 3],
+                        ));
+                    }
+                    (top, bottom) => {
+                        output.push_str(&color.escape(self.color_of(top)?, false));
+                        if let Some(bottom) = bottom {
+                            output.push_str(&color.escape(self.color_of(bottom)?, true));
+                        }
+                        output.push_str("▀\x1b[0m");
+                    }
+                }
+            }
+        }
+
+        Ok(output)
+    }
+
+    // draws cells of `width`x`height` pixels, `glyph` gets the lit pixels of
+    // a cell as bits in row after row order
+    fn render_cells(
+        &self,
+        width: usize,
+        height: usize,
+        glyph: impl Fn(u8) -> char,
+    ) -> Result<String> {
+        let lit: Vec<Vec<bool>> = self
+            .rows()?
+            .map(|row| {
+                row.into_iter()
+                    .map(|pixel| match pixel {
+                        Pixel::Bw(white) => Ok(white),
+                        pixel => Ok(luminance(self.color_of(pixel)?) >= 128),
+                    })
+                    .collect::<Result<_>>()
+            })
+            .collect::<Result<_>>()?;
+
+        let mut output = String::new();
+        for cells in lit.chunks(height) {
+            output.push('\n');
+            for x in (0..self.header.width as usize).step_by(width) {
+                let mut bits = 0u8;
+                for (dy, row) in cells.iter().enumerate() {
+                    for dx in 0..width {
+                        if row.get(x + dx) == Some(&true) {
+                            bits |= 1 << (dy * width + dx);
+                        }
+                    }
+                }
+                output.push(glyph(bits));
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+// pixels drawn by a character, horizontally and vertically
+fn pixels_per_char(mode: RenderMode, black_and_white: bool) -> (f64, f64) {
+    match mode {
+        RenderMode::Full if black_and_white => (1.0, 1.0),
+        // two characters per pixel
+        RenderMode::Full => (0.5, 1.0),
+        RenderMode::HalfBlock => (1.0, 2.0),
+        RenderMode::Quadrant => (2.0, 2.0),
+        RenderMode::Braille => (2.0, 4.0),
+    }
+}
@@ -0,0 +1,67 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::art;
+    use crate::test_utils::sample;
+    use crate::{MiniPNG, RenderMode, RenderOptions};
+
+    fn render(image: &MiniPNG, mode: RenderMode) -> String {
+        image
+            .render(&RenderOptions {
+                mode,
+                ..RenderOptions::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_full_matches_display() {
+        let image = sample(include_bytes!("../minipng-samples/other/ok/french-flag.mp"));
+        let display = image.display().unwrap();
+        assert!(display.ends_with(&render(&image, RenderMode::Full)));
+        assert!(display.starts_with("Mini-PNG Image\n"));
+    }
+
+    #[test]
+    fn test_render_half_blocks_black_and_white() {
+        let image = art("X X\n  X\nXX \n");
+        // the last line only has upper pixels
+        assert_eq!(render(&image, RenderMode::HalfBlock), "\n▀ █\n▀▀ ");
+    }
+
+    #[test]
+    fn test_render_half_blocks_colors() {
+        let image = art("rgb\nr #ff0000\nb #0000ff\n\nrb\nbr\nrb\n");
+        let output = render(&image, RenderMode::HalfBlock);
+        let lines: Vec<&str> = output.lines().skip(1).collect();
+        assert_eq!(
+            lines[0],
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m▀\x1b[0m\x1b[38;2;0;0;255m\x1b[48;2;255;0;0m▀\x1b[0m"
+        );
+        // the last line only has upper pixels
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("\x1b[38;2;255;0;0m▀\x1b[0m"));
+        assert!(!lines[1].contains("\x1b[48;"));
+    }
+
+    #[test]
+    fn test_render_quadrants() {
+        let image = art("X  X\n XX \nX\n");
+        assert_eq!(render(&image, RenderMode::Quadrant), "\n▚▞\n▘ ");
+    }
+
+    #[test]
+    fn test_render_braille() {
+        let image = art("X X\nX\n X\n XX\nXXX\n");
+        assert_eq!(
+            render(&image, RenderMode::Braille),
+            "\n\u{28a3}\u{2841}\n\u{2809}\u{2801}"
+        );
+    }
+
+    #[test]
+    fn test_render_cells_use_luminance() {
+        let image = art("gray\n\n@ \n.:\n");
+        assert_eq!(render(&image, RenderMode::Quadrant), "\n▘");
+        assert_eq!(render(&image, RenderMode::Braille), "\n\u{2801}");
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::Result;
+use crate::mininpng::{Header, MiniPNG};
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+
+/// How black and white pixels are merged when an image is downscaled.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BwScaling {
+    /// The most common color of the merged pixels, white on a tie.
+    #[default]
+    Majority,
+    /// The share of white pixels as a gray level.
+    Coverage,
+}
+
+impl MiniPNG {
+    /// Shrinks the image to `width`x`height` pixels, each pixel averaging
+    /// the block of pixels it covers.
+    ///
+    /// Gray level and RGB images keep their pixel type while palette images
+    /// become RGB as averaged colors are rarely in the palette. Black and
+    /// white images are merged according to `bw`, [`BwScaling::Coverage`]
+    /// giving a gray level image. Dimensions larger than the ones of the
+    /// image are clamped to them.
+    pub fn downscale(&self, width: u32, height: u32, bw: BwScaling) -> Result<MiniPNG> {
+        let (source_width, source_height) = (self.header.width, self.header.height);
+        let width = width.min(source_width);
+        let height = height.min(source_height);
+
+        let pixel_type = match (self.header.pixel_type, bw) {
+            (PixelType::BlackAndWhite, BwScaling::Coverage) => PixelType::GrayLevels,
+            (PixelType::Palette, _) => PixelType::RGB,
+            (pixel_type, _) => pixel_type,
+        };
+        let colors: Vec<[u8; 3]> = self
+            .pixels()?
+            .map(|pixel| self.color_of(pixel))
+            .collect::<Result<_>>()?;
+
+        let mut image = MiniPNG::new(
+            Header {
+                width,
+                height,
+                pixel_type,
+            },
+            None,
+        )?;
+        image.comments = self.comments.clone();
+
+        for y in 0..height {
+            let rows = span(y, height, source_height);
+            for x in 0..width {
+                let columns = span(x, width, source_width);
+
+                let mut sum = [0u64; 3];
+                for sy in rows.clone() {
+                    for sx in columns.clone() {
+                        let color = colors[sy as usize * source_width as usize + sx as usize];
+                        for (total, channel) in sum.iter_mut().zip(color) {
+                            *total += channel as u64;
+                        }
+                    }
+                }
+                let count = rows.len() as u64 * columns.len() as u64;
+                let average = sum.map(|total| ((total + count / 2) / count) as u8);
+
+                let pixel = match pixel_type {
+                    // white pixels sum to 255 each
+                    PixelType::BlackAndWhite => Pixel::Bw(sum[0] * 2 >= 255 * count),
+                    PixelType::GrayLevels => Pixel::Gray(average[0]),
+                    _ => Pixel::Rgb(average),
+                };
+                image.set_pixel(x, y, pixel)?;
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+// source pixels covered by the target pixel `i` out of `target`, never empty
+fn span(i: u32, target: u32, source: u32) -> std::ops::Range<u32> {
+    let start = (i as u64 * source as u64 / target as u64) as u32;
+    let end = ((i as u64 + 1) * source as u64 / target as u64) as u32;
+    start..end.max(start + 1)
+}
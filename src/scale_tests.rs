@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::art;
+    use crate::{BwScaling, Pixel, PixelType, RenderMode, RenderOptions};
+
+    #[test]
+    fn test_downscale_averages_blocks() {
+        let image = art("gray 0123\n\n0011\n2233\n");
+        let scaled = image.downscale(2, 1, BwScaling::Majority).unwrap();
+        assert_eq!(scaled.header.pixel_type, PixelType::GrayLevels);
+        // (0 + 0 + 170 + 170) / 4 and (85 + 85 + 255 + 255) / 4
+        assert_eq!(scaled.data, vec![85, 170]);
+    }
+
+    #[test]
+    fn test_downscale_uneven_blocks() {
+        let image = art("gray 0123\n\n01233\n");
+        let scaled = image.downscale(2, 1, BwScaling::Majority).unwrap();
+        // the first pixel covers 2 pixels and the second one 3
+        assert_eq!(scaled.data, vec![43, 227]);
+
+        // larger dimensions are clamped
+        let same = image.downscale(10, 10, BwScaling::Majority).unwrap();
+        assert_eq!(same.data, image.data);
+    }
+
+    #[test]
+    fn test_downscale_palette_becomes_rgb() {
+        let image = art("palette\nr #ff0000\nb #0000ff\n\nrb\n");
+        let scaled = image.downscale(1, 1, BwScaling::Majority).unwrap();
+        assert_eq!(scaled.header.pixel_type, PixelType::RGB);
+        assert_eq!(scaled.palette, None);
+        assert_eq!(scaled.get_pixel(0, 0).unwrap(), Pixel::Rgb([128, 0, 128]));
+    }
+
+    #[test]
+    fn test_downscale_black_and_white() {
+        let image = art("XX  X\nX   X\n");
+        let majority = image.downscale(2, 1, BwScaling::Majority).unwrap();
+        assert_eq!(majority.header.pixel_type, PixelType::BlackAndWhite);
+        // 3 out of 4 and 2 out of 6 white pixels
+        assert_eq!(
+            majority.pixels().unwrap().collect::<Vec<_>>(),
+            [Pixel::Bw(true), Pixel::Bw(false)]
+        );
+
+        let coverage = image.downscale(2, 1, BwScaling::Coverage).unwrap();
+        assert_eq!(coverage.header.pixel_type, PixelType::GrayLevels);
+        assert_eq!(coverage.data, vec![191, 85]);
+    }
+
+    #[test]
+    fn test_render_fits_the_terminal() {
+        let image = art(&format!("{}\n", "X".repeat(40)).repeat(20));
+        let render = |mode, fit, bw_scaling| {
+            image
+                .render(&RenderOptions {
+                    mode,
+                    fit: Some(fit),
+                    bw_scaling,
+                    ..RenderOptions::default()
+                })
+                .unwrap()
+        };
+        let size = |output: String| {
+            let lines: Vec<&str> = output.lines().skip(1).collect();
+            (lines[0].chars().count(), lines.len())
+        };
+
+        // the aspect ratio is kept
+        assert_eq!(
+            size(render(RenderMode::Full, (20, 50), BwScaling::Majority)),
+            (20, 10)
+        );
+        assert_eq!(
+            size(render(RenderMode::Full, (80, 50), BwScaling::Majority)),
+            (40, 20)
+        );
+        assert_eq!(
+            size(render(RenderMode::HalfBlock, (80, 5), BwScaling::Majority)),
+            (20, 5)
+        );
+        assert_eq!(
+            size(render(RenderMode::Braille, (10, 50), BwScaling::Majority)),
+            (10, 3)
+        );
+
+        // gray pixels take two characters
+        let output = render(RenderMode::Full, (20, 50), BwScaling::Coverage);
+        assert_eq!(output.lines().count(), 6);
+    }
+
+    #[test]
+    fn test_display_fit_leaves_room_for_the_summary() {
+        let mut image = art(&format!("{}\n", "X".repeat(40)).repeat(20));
+        image.comments.push("a comment".to_string());
+        let options = RenderOptions {
+            fit: Some((80, 20)),
+            ..RenderOptions::default()
+        };
+
+        // 7 summary lines and an empty one leave 12 lines for the pixels
+        let output = image.display_with(&options).unwrap();
+        assert_eq!(output.lines().count(), 20);
+        assert_eq!(output.lines().last().unwrap(), "X".repeat(24));
+    }
+}
@@ -0,0 +1,138 @@
+use std::env;
+
+use crate::convert::luminance;
+use crate::text_art::DEFAULT_RAMP;
+
+// xterm's default values of the 16 ANSI colors
+const ANSI_COLORS: [[u8; 3]; 16] = [
+    [0, 0, 0],
+    [205, 0, 0],
+    [0, 205, 0],
+    [205, 205, 0],
+    [0, 0, 238],
+    [205, 0, 205],
+    [0, 205, 205],
+    [229, 229, 229],
+    [127, 127, 127],
+    [255, 0, 0],
+    [0, 255, 0],
+    [255, 255, 0],
+    [92, 92, 255],
+    [255, 0, 255],
+    [0, 255, 255],
+    [255, 255, 255],
+];
+// levels of each channel in the 6x6x6 cube of the 256 colors
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Colors a terminal is able to show.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorSupport {
+    /// 24 bit colors.
+    #[default]
+    TrueColor,
+    /// The xterm 256 colors palette.
+    Ansi256,
+    /// The 16 ANSI colors.
+    Ansi16,
+    /// No colors, pixels are drawn with characters of [`DEFAULT_RAMP`].
+    Monochrome,
+}
+
+impl ColorSupport {
+    /// Guesses the colors of the terminal from the environment.
+    pub fn detect() -> Self {
+        Self::from_env(|name| env::var(name).ok())
+    }
+
+    /// Guesses the colors of a terminal from `NO_COLOR`, `COLORTERM` and
+    /// `TERM`, read with `var`.
+    ///
+    /// A non-empty `NO_COLOR`, or a missing or `dumb` `TERM`, disables the
+    /// colors.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorSupport::Monochrome;
+        }
+        if let Some(colorterm) = var("COLORTERM")
+            && matches!(colorterm.as_str(), "truecolor" | "24bit")
+        {
+            return ColorSupport::TrueColor;
+        }
+
+        match var("TERM") {
+            None => ColorSupport::Monochrome,
+            Some(term) if term.is_empty() || term == "dumb" => ColorSupport::Monochrome,
+            Some(term) if term.contains("256color") => ColorSupport::Ansi256,
+            Some(_) => ColorSupport::Ansi16,
+        }
+    }
+
+    /// Escape sequence setting the foreground color, or the background color
+    /// when `background` is set. It is empty for [`ColorSupport::Monochrome`].
+    pub fn escape(self, color: [u8; 3], background: bool) -> String {
+        let [r, g, b] = color;
+        let layer = if background { 48 } else { 38 };
+
+        match self {
+            ColorSupport::TrueColor => format!("\x1b[{};2;{};{};{}m", layer, r, g, b),
+            ColorSupport::Ansi256 => format!("\x1b[{};5;{}m", layer, ansi256(color)),
+            ColorSupport::Ansi16 => {
+                let index = nearest(&ANSI_COLORS, color) as u8;
+                // bright colors have their own codes
+                let code = match (index < 8, background) {
+                    (true, false) => 30 + index,
+                    (true, true) => 40 + index,
+                    (false, false) => 90 + index - 8,
+                    (false, true) => 100 + index - 8,
+                };
+                format!("\x1b[{}m", code)
+            }
+            ColorSupport::Monochrome => String::new(),
+        }
+    }
+}
+
+// characters of `DEFAULT_RAMP`, collected once per rendered image
+pub(crate) fn ramp() -> Vec<char> {
+    DEFAULT_RAMP.chars().collect()
+}
+
+// character of `ramp` drawing a color without escape sequences
+pub(crate) fn ramp_char(ramp: &[char], color: [u8; 3]) -> char {
+    ramp[luminance(color) as usize * (ramp.len() - 1) / 255]
+}
+
+// index of the nearest color of the xterm 256 colors palette, taken from the
+// 6x6x6 cube or from the gray ramp
+fn ansi256(color: [u8; 3]) -> u8 {
+    let levels = color.map(|channel| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(channel))
+            .unwrap_or(0)
+    });
+    let cube = levels.map(|level| CUBE_LEVELS[level]);
+
+    let average = color.iter().map(|&channel| channel as usize).sum::<usize>() / 3;
+    let gray_index = (average.saturating_sub(8) / 10).min(23);
+    let gray = [(8 + gray_index * 10) as u8; 3];
+
+    if distance(gray, color) < distance(cube, color) {
+        232 + gray_index as u8
+    } else {
+        (16 + levels[0] * 36 + levels[1] * 6 + levels[2]) as u8
+    }
+}
+
+fn nearest(colors: &[[u8; 3]], color: [u8; 3]) -> usize {
+    (0..colors.len())
+        .min_by_key(|&i| distance(colors[i], color))
+        .unwrap_or(0)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32)
+        .sum()
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::art;
+    use crate::{ColorSupport, RenderMode, RenderOptions};
+
+    fn detect(vars: &[(&str, &str)]) -> ColorSupport {
+        ColorSupport::from_env(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn test_color_support_detection() {
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(detect(&[("COLORTERM", "24bit")]), ColorSupport::TrueColor);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorSupport::Ansi256);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorSupport::Ansi16);
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorSupport::Monochrome);
+        assert_eq!(detect(&[]), ColorSupport::Monochrome);
+
+        // NO_COLOR wins over everything else, unless it is empty
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorSupport::Monochrome
+        );
+        assert_eq!(
+            detect(&[("NO_COLOR", ""), ("TERM", "xterm-256color")]),
+            ColorSupport::Ansi256
+        );
+    }
+
+    #[test]
+    fn test_color_escapes() {
+        assert_eq!(
+            ColorSupport::TrueColor.escape([1, 2, 3], false),
+            "\x1b[38;2;1;2;3m"
+        );
+        assert_eq!(
+            ColorSupport::TrueColor.escape([1, 2, 3], true),
+            "\x1b[48;2;1;2;3m"
+        );
+
+        // colors of the cube and of the gray ramp
+        assert_eq!(
+            ColorSupport::Ansi256.escape([255, 0, 0], false),
+            "\x1b[38;5;196m"
+        );
+        assert_eq!(
+            ColorSupport::Ansi256.escape([128, 128, 128], true),
+            "\x1b[48;5;244m"
+        );
+        assert_eq!(
+            ColorSupport::Ansi256.escape([0, 95, 135], false),
+            "\x1b[38;5;24m"
+        );
+
+        assert_eq!(ColorSupport::Ansi16.escape([0, 0, 200], false), "\x1b[34m");
+        assert_eq!(
+            ColorSupport::Ansi16.escape([250, 10, 10], false),
+            "\x1b[91m"
+        );
+        assert_eq!(
+            ColorSupport::Ansi16.escape([250, 10, 10], true),
+            "\x1b[101m"
+        );
+
+        assert_eq!(ColorSupport::Monochrome.escape([250, 10, 10], false), "");
+    }
+
+    #[test]
+    fn test_render_monochrome() {
+        let image = art("gray\n\n .:@\n@@@@\n");
+        let render = |mode| {
+            image
+                .render(&RenderOptions {
+                    mode,
+                    color: ColorSupport::Monochrome,
+                    ..RenderOptions::default()
+                })
+                .unwrap()
+        };
+
+        assert_eq!(render(RenderMode::Full), "\n    ::@@\n@@@@@@@@");
+        // the two pixels of a half block are averaged
+        assert_eq!(render(RenderMode::HalfBlock), "\n==+@");
+    }
+
+    #[test]
+    fn test_render_reduced_colors() {
+        let image = art("rgb\nr #ff0000\n\nr\n");
+        let output = image
+            .render(&RenderOptions {
+                mode: RenderMode::Full,
+                color: ColorSupport::Ansi16,
+                ..RenderOptions::default()
+            })
+            .unwrap();
+        assert_eq!(output, "\n\x1b[91m██\x1b[0m");
+    }
+}
@@ -0,0 +1,35 @@
+// helpers shared by the test modules
+
+use crate::mininpng::{BwChars, Header, MiniPNG};
+use crate::pixel_type::PixelType;
+
+// Mini-PNG file made of the magic number and `blocks`, written as is so
+// malformed files can be built too
+pub(crate) fn file(blocks: &[(u8, &[u8])]) -> Vec<u8> {
+    let mut bytes = b"Mini-PNG".to_vec();
+    for (block_type, content) in blocks {
+        bytes.push(*block_type);
+        bytes.extend_from_slice(&(content.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(content);
+    }
+    bytes
+}
+
+// decodes a sample file, usually given with `include_bytes!`
+pub(crate) fn sample(bytes: &[u8]) -> MiniPNG {
+    MiniPNG::read_from(bytes).unwrap()
+}
+
+// header of a `width`x`height` image of `pixel_type`
+pub(crate) fn header(width: u32, height: u32, pixel_type: PixelType) -> Header {
+    Header {
+        width,
+        height,
+        pixel_type,
+    }
+}
+
+// parses text art drawn with the default characters
+pub(crate) fn art(input: &str) -> MiniPNG {
+    MiniPNG::from_text_art(input, BwChars::default()).unwrap()
+}
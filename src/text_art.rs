@@ -0,0 +1,219 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::{BwChars, Header, MiniPNG, Palette, dimension};
+use crate::pixel::Pixel;
+use crate::pixel_type::PixelType;
+
+/// Gray ramp used by a `gray` header without legend, from black to white.
+pub const DEFAULT_RAMP: &str = " .:-=+*#%@";
+
+impl MiniPNG {
+    /// Creates an image from text art.
+    ///
+    /// Text starting with a `gray`, `palette` or `rgb` line is made of a
+    /// legend, one `<char> <value>` line per character, then an empty line
+    /// and the pixel grid. Values are `#rrggbb` colors, or gray levels from
+    /// 0 to 255 for `gray`. The palette keeps the order of the legend.
+    ///
+    /// ```text
+    /// palette
+    /// R #ff0000
+    /// . #000000
+    ///
+    /// R.R
+    /// .R.
+    /// ```
+    ///
+    /// A `gray` line may be followed by a ramp of characters spread evenly
+    /// from black to white, such as `gray  .:-=+*#%@`, and a bare `gray`
+    /// line uses [`DEFAULT_RAMP`]. Shorter grid lines are padded with the
+    /// first character of the ramp or of the legend. Any other text is black
+    /// and white art read by [`MiniPNG::bw_from_string_with`].
+    pub fn from_text_art(input: &str, chars: BwChars) -> Result<Self> {
+        let mut lines = input.lines().enumerate();
+        let Some((_, first)) = lines.next() else {
+            return Self::bw_from_string_with(input, chars);
+        };
+
+        let (pixel_type, ramp) = match first.split_once(' ') {
+            _ if first == "palette" => (PixelType::Palette, None),
+            _ if first == "rgb" => (PixelType::RGB, None),
+            _ if first == "gray" => (PixelType::GrayLevels, Some(DEFAULT_RAMP)),
+            Some(("gray", ramp)) => (PixelType::GrayLevels, Some(ramp)),
+            _ => return Self::bw_from_string_with(input, chars),
+        };
+
+        let mut legend: Vec<(char, [u8; 3])> = Vec::new();
+        if let Some(ramp) = ramp {
+            let steps = ramp.chars().count().max(2) - 1;
+            for (i, c) in ramp.chars().enumerate() {
+                let value = ((i * 255 + steps / 2) / steps) as u8;
+                legend.push((c, [value; 3]));
+            }
+        }
+
+        for (index, line) in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let mut line_chars = line.chars();
+            let c = line_chars.next().unwrap_or(' ');
+            let value = line_chars.as_str().trim();
+            let color = match pixel_type {
+                PixelType::GrayLevels => value.parse::<u8>().map(|v| [v; 3]).ok(),
+                _ => parse_color(value),
+            }
+            .ok_or(MiniPngError::InvalidTextArt {
+                line: index + 1,
+                reason: "invalid legend value",
+            })?;
+
+            // a legend line overrides the ramp
+            match legend.iter_mut().find(|(existing, _)| *existing == c) {
+                Some(entry) if pixel_type == PixelType::GrayLevels => entry.1 = color,
+                Some(_) => {
+                    return Err(MiniPngError::InvalidTextArt {
+                        line: index + 1,
+                        reason: "character defined twice",
+                    });
+                }
+                None => legend.push((c, color)),
+            }
+        }
+
+        let grid: Vec<(usize, Vec<char>)> =
+            lines.map(|(i, line)| (i, line.chars().collect())).collect();
+        let header = Header {
+            width: dimension(
+                "width",
+                grid.iter().map(|(_, row)| row.len()).max().unwrap_or(0),
+            )?,
+            height: dimension("height", grid.len())?,
+            pixel_type,
+        };
+
+        let palette = match pixel_type {
+            PixelType::Palette if legend.len() > 256 => {
+                return Err(MiniPngError::TooManyColors(legend.len()));
+            }
+            PixelType::Palette => Some(Palette {
+                colors: legend.iter().map(|&(_, color)| color).collect(),
+            }),
+            _ => None,
+        };
+        let pixels: HashMap<char, Pixel> = legend
+            .iter()
+            .enumerate()
+            .map(|(i, &(c, color))| {
+                let pixel = match pixel_type {
+                    PixelType::GrayLevels => Pixel::Gray(color[0]),
+                    PixelType::Palette => Pixel::Index(i as u8),
+                    _ => Pixel::Rgb(color),
+                };
+                (c, pixel)
+            })
+            .collect();
+
+        // shorter lines are padded with the first legend character
+        let padding = legend.first().map(|(c, _)| pixels[c]);
+
+        let mut image = MiniPNG::new(header, palette)?;
+        for (y, (index, row)) in grid.iter().enumerate() {
+            for x in 0..image.header.width as usize {
+                let pixel = match row.get(x) {
+                    Some(c) => pixels.get(c).copied(),
+                    None => padding,
+                }
+                .ok_or(MiniPngError::InvalidTextArt {
+                    line: index + 1,
+                    reason: "character missing from the legend",
+                })?;
+                image.set_pixel(x as u32, y as u32, pixel)?;
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Writes the image as the text art read by [`MiniPNG::from_text_art`].
+    ///
+    /// Black and white images are written by [`MiniPNG::bw_to_string_with`].
+    /// Other images get a legend with one printable character per gray
+    /// level or color, which fails with [`MiniPngError::TooManyColors`] when
+    /// there are not enough characters.
+    pub fn to_text_art(&self, chars: BwChars) -> Result<String> {
+        let (header, values): (&str, Vec<[u8; 3]>) = match self.header.pixel_type {
+            PixelType::BlackAndWhite => return self.bw_to_string_with(chars),
+            PixelType::Palette => (
+                "palette",
+                self.palette
+                    .as_ref()
+                    .ok_or(MiniPngError::MissingPalette)?
+                    .colors
+                    .clone(),
+            ),
+            pixel_type => {
+                // sorted so that gray levels go from dark to light
+                let mut values = BTreeSet::new();
+                for pixel in self.pixels()? {
+                    values.insert(self.color_of(pixel)?);
+                }
+                let values = values.into_iter().collect();
+                match pixel_type {
+                    PixelType::GrayLevels => ("gray", values),
+                    _ => ("rgb", values),
+                }
+            }
+        };
+
+        let symbols: Vec<char> = legend_chars().take(values.len()).collect();
+        if symbols.len() < values.len() {
+            return Err(MiniPngError::TooManyColors(values.len()));
+        }
+
+        let mut output = format!("{}\n", header);
+        for (c, [r, g, b]) in symbols.iter().zip(&values) {
+            match self.header.pixel_type {
+                PixelType::GrayLevels => output.push_str(&format!("{} {}\n", c, r)),
+                _ => output.push_str(&format!("{} #{:02x}{:02x}{:02x}\n", c, r, g, b)),
+            }
+        }
+        output.push('\n');
+
+        let symbol_of: HashMap<[u8; 3], char> = values
+            .iter()
+            .copied()
+            .zip(symbols.iter().copied())
+            .collect();
+        for row in self.rows()? {
+            for pixel in row {
+                let c = match pixel {
+                    Pixel::Index(index) => *symbols
+                        .get(index as usize)
+                        .ok_or(MiniPngError::InvalidPaletteIndex(index))?,
+                    pixel => symbol_of[&self.color_of(pixel)?],
+                };
+                output.push(c);
+            }
+            output.push('\n');
+        }
+
+        Ok(output)
+    }
+}
+
+// `#rrggbb`
+fn parse_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// printable characters of ASCII and Latin-1, then Latin Extended
+fn legend_chars() -> impl Iterator<Item = char> {
+    ('!'..='~').chain('¡'..='ɏ').filter(|&c| c != '\u{ad}')
+}
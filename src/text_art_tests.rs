@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::sample;
+    use crate::{BwChars, MiniPNG, MiniPngError, Palette, Pixel, PixelType};
+
+    #[test]
+    fn test_text_art_palette() {
+        let input = "palette\nR #ff0000\n  #000000\nw #FFFFFF\n\nR R\n wR\nR\n";
+        let image = MiniPNG::from_text_art(input, BwChars::default()).unwrap();
+
+        assert_eq!(image.header.pixel_type, PixelType::Palette);
+        assert_eq!((image.header.width, image.header.height), (3, 3));
+        assert_eq!(
+            image.palette,
+            Some(Palette {
+                colors: vec![[255, 0, 0], [0, 0, 0], [255, 255, 255]]
+            })
+        );
+        // the last line is padded with the first legend character
+        assert_eq!(image.data, vec![0, 1, 0, 1, 2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_text_art_rgb() {
+        let input = "rgb\na #102030\nb #0000ff\n\nab\nba\n";
+        let image = MiniPNG::from_text_art(input, BwChars::default()).unwrap();
+
+        assert_eq!(image.header.pixel_type, PixelType::RGB);
+        assert_eq!(image.get_pixel(0, 0).unwrap(), Pixel::Rgb([16, 32, 48]));
+        assert_eq!(image.get_pixel(0, 1).unwrap(), Pixel::Rgb([0, 0, 255]));
+    }
+
+    #[test]
+    fn test_text_art_gray_ramps() {
+        // the default ramp goes from black to white
+        let image = MiniPNG::from_text_art("gray\n\n .:@\n", BwChars::default()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::GrayLevels);
+        assert_eq!(image.data, vec![0, 28, 57, 255]);
+
+        // a custom ramp, with a legend line overriding one of its levels
+        let image = MiniPNG::from_text_art("gray abc\nb 100\n\ncba\n", BwChars::default()).unwrap();
+        assert_eq!(image.data, vec![255, 100, 0]);
+    }
+
+    #[test]
+    fn test_text_art_without_header_is_black_and_white() {
+        let image = MiniPNG::from_text_art("X \n X\n", BwChars::default()).unwrap();
+        assert_eq!(image.header.pixel_type, PixelType::BlackAndWhite);
+        assert_eq!(image.data, MiniPNG::bw_from_string("X \n X").unwrap().data);
+
+        let chars = BwChars {
+            white: '#',
+            black: '.',
+        };
+        let image = MiniPNG::from_text_art("#.", chars).unwrap();
+        assert_eq!(image.get_pixel(0, 0).unwrap(), Pixel::Bw(true));
+    }
+
+    #[test]
+    fn test_text_art_padding_without_space() {
+        let image =
+            MiniPNG::from_text_art("rgb\na #ff0000\nb #0000ff\n\nbab\nb\n", BwChars::default())
+                .unwrap();
+        assert_eq!(image.get_pixel(2, 1).unwrap(), Pixel::Rgb([255, 0, 0]));
+
+        // the ramp comes before the legend lines
+        let image = MiniPNG::from_text_art("gray ab\nc 100\n\nbc\n\n", BwChars::default()).unwrap();
+        assert_eq!(image.data, vec![255, 100, 0, 0]);
+    }
+
+    #[test]
+    fn test_text_art_errors() {
+        assert!(matches!(
+            MiniPNG::from_text_art("rgb\na red\n\na", BwChars::default()),
+            Err(MiniPngError::InvalidTextArt { line: 2, .. })
+        ));
+        assert!(matches!(
+            MiniPNG::from_text_art("gray\na 256\n\na", BwChars::default()),
+            Err(MiniPngError::InvalidTextArt { line: 2, .. })
+        ));
+        assert!(matches!(
+            MiniPNG::from_text_art("palette\na #000000\na #ffffff\n\na", BwChars::default()),
+            Err(MiniPngError::InvalidTextArt { line: 3, .. })
+        ));
+        // grid errors point to their line
+        assert!(matches!(
+            MiniPNG::from_text_art("palette\na #000000\n\naa\nab", BwChars::default()),
+            Err(MiniPngError::InvalidTextArt { line: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_text_art_round_trips() {
+        for bytes in [
+            include_bytes!("../minipng-samples/bw/ok/A.mp").as_slice(),
+            include_bytes!("../minipng-samples/other/ok/gray.mp"),
+            include_bytes!("../minipng-samples/other/ok/french-flag.mp"),
+            include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp"),
+        ] {
+            let image = sample(bytes);
+            let text = image.to_text_art(BwChars::default()).unwrap();
+            let decoded = MiniPNG::from_text_art(&text, BwChars::default()).unwrap();
+            assert_eq!(decoded.header, image.header);
+            assert_eq!(decoded.palette, image.palette);
+            assert!(decoded.pixels().unwrap().eq(image.pixels().unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_to_text_art_legend() {
+        let image = MiniPNG::from_text_art("rgb\nz #ffffff\ny #000000\n\nzy\n", BwChars::default())
+            .unwrap();
+        // the legend is rewritten in increasing order
+        assert_eq!(
+            image.to_text_art(BwChars::default()).unwrap(),
+            "rgb\n! #000000\n\" #ffffff\n\n\"!\n"
+        );
+    }
+}
@@ -0,0 +1,14 @@
+// minimal TOML serialization helpers, the crate only ever writes TOML
+
+use crate::json;
+
+// quotes and escapes `s` as a TOML basic string
+pub(crate) fn string(s: &str) -> String {
+    // JSON escapes are valid in TOML basic strings, which also forbid DEL
+    json::string(s).replace('\u{7f}', "\\u007f")
+}
+
+// joins already serialized values into a TOML array
+pub(crate) fn array(values: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", values.into_iter().collect::<Vec<_>>().join(", "))
+}
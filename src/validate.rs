@@ -0,0 +1,203 @@
+use std::fmt::Display;
+
+use crate::borrowed::{MiniPngRef, RawBlocks};
+use crate::error::{MiniPngError, Result};
+use crate::json;
+use crate::pixel_type::PixelType;
+
+/// A non-fatal deviation from the format found by [`validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// Bytes after the last complete block, too few to start another one.
+    TrailingBytes {
+        /// Offset of the first trailing byte.
+        offset: usize,
+        /// Number of trailing bytes.
+        count: usize,
+    },
+    /// A header block longer than the 9 bytes it needs.
+    LongHeader {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Length of the block content.
+        length: usize,
+    },
+    /// A palette block whose length isn't a multiple of 3.
+    PartialPaletteColor {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+        /// Length of the block content.
+        length: usize,
+    },
+    /// A comment that is valid UTF-8 but not ASCII as the spec requires.
+    NonAsciiComment {
+        /// Position of the block in the file, starting at 0.
+        index: usize,
+    },
+    /// Palette entries that no pixel refers to.
+    UnusedPaletteEntries {
+        /// Indices of the unused colors, in increasing order.
+        entries: Vec<usize>,
+    },
+}
+
+impl Finding {
+    /// Short identifier of the finding, stable for scripts.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Finding::TrailingBytes { .. } => "trailing-bytes",
+            Finding::LongHeader { .. } => "long-header",
+            Finding::PartialPaletteColor { .. } => "partial-palette-color",
+            Finding::NonAsciiComment { .. } => "non-ascii-comment",
+            Finding::UnusedPaletteEntries { .. } => "unused-palette-entries",
+        }
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Finding::TrailingBytes { offset, count } => {
+                write!(f, "{} trailing bytes at offset {:#x}", count, offset)
+            }
+            Finding::LongHeader { index, length } => write!(
+                f,
+                "header block #{} is {} bytes long, only 9 are used",
+                index, length
+            ),
+            Finding::PartialPaletteColor { index, length } => write!(
+                f,
+                "palette block #{} is {} bytes long, which is not a multiple of 3",
+                index, length
+            ),
+            Finding::NonAsciiComment { index } => {
+                write!(f, "comment block #{} is not ASCII", index)
+            }
+            Finding::UnusedPaletteEntries { entries } => write!(
+                f,
+                "{} unused palette entries: {}",
+                entries.len(),
+                entries
+                    .iter()
+                    .map(|entry| entry.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+/// Conformance report of a single file.
+#[derive(Debug)]
+pub struct Report {
+    /// Why the file is rejected by the parser, if it is.
+    pub error: Option<MiniPngError>,
+    /// Deviations that don't prevent decoding.
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    /// Whether the file is accepted by the parser.
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Serializes the report as a JSON object, `path` identifies the file.
+    pub fn to_json(&self, path: &str) -> String {
+        let error = match &self.error {
+            Some(error) => json::string(&error.to_string()),
+            None => "null".to_string(),
+        };
+        let findings = json::array(self.findings.iter().map(|finding| {
+            format!(
+                "{{\"kind\":{},\"message\":{}}}",
+                json::string(finding.kind()),
+                json::string(&finding.to_string())
+            )
+        }));
+
+        format!(
+            "{{\"path\":{},\"valid\":{},\"error\":{},\"findings\":{}}}",
+            json::string(path),
+            self.is_valid(),
+            error,
+            findings
+        )
+    }
+}
+
+/// Runs the parser on `bytes` and collects the non-fatal findings.
+///
+/// On top of the parser checks, palette images are rejected if a pixel
+/// refers to a color outside of the palette.
+pub fn validate(bytes: &[u8]) -> Report {
+    let mut findings = block_findings(bytes);
+    let error = check_image(bytes, &mut findings).err();
+
+    Report { error, findings }
+}
+
+// findings that only need the block framing, so they are also reported for
+// files the parser rejects
+fn block_findings(bytes: &[u8]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let Ok(blocks) = RawBlocks::new(bytes) else {
+        return findings;
+    };
+
+    for block in blocks {
+        let block = match block {
+            Ok(block) => block,
+            // fewer than 5 bytes can't hold a block type and length, longer
+            // broken blocks are left to the parse error
+            Err(MiniPngError::InBlock { offset, .. }) if bytes.len() - (offset as usize) < 5 => {
+                findings.push(Finding::TrailingBytes {
+                    offset: offset as usize,
+                    count: bytes.len() - offset as usize,
+                });
+                break;
+            }
+            Err(_) => break,
+        };
+
+        let length = block.content.len();
+        match block.block_type {
+            b'H' if length > 9 => findings.push(Finding::LongHeader {
+                index: block.index,
+                length,
+            }),
+            b'P' if length % 3 != 0 => findings.push(Finding::PartialPaletteColor {
+                index: block.index,
+                length,
+            }),
+            b'C' if !block.content.is_ascii() => {
+                findings.push(Finding::NonAsciiComment { index: block.index })
+            }
+            _ => {}
+        }
+    }
+
+    findings
+}
+
+fn check_image(bytes: &[u8], findings: &mut Vec<Finding>) -> Result<()> {
+    let image = MiniPngRef::parse(bytes)?;
+    if image.header.pixel_type != PixelType::Palette {
+        return Ok(());
+    }
+
+    let palette_size = image.palette_colors().count();
+    let mut used = vec![false; palette_size];
+    for index in image.data() {
+        *used
+            .get_mut(index as usize)
+            .ok_or(MiniPngError::InvalidPaletteIndex(index))? = true;
+    }
+
+    let entries: Vec<usize> = (0..palette_size).filter(|&i| !used[i]).collect();
+    if !entries.is_empty() {
+        findings.push(Finding::UnusedPaletteEntries { entries });
+    }
+
+    Ok(())
+}
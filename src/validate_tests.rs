@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::file;
+    use crate::{Finding, MiniPngError, validate};
+
+    #[test]
+    fn test_validate_valid_file() {
+        let report = validate(include_bytes!("../minipng-samples/bw/ok/A.mp"));
+        assert!(report.is_valid());
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_out_of_palette_index() {
+        let report = validate(include_bytes!(
+            "../minipng-samples/other/nok/german-flag-error-palette.mp"
+        ));
+        assert!(matches!(
+            report.error,
+            Some(MiniPngError::InvalidPaletteIndex(2))
+        ));
+    }
+
+    #[test]
+    fn test_validate_findings() {
+        let mut bytes = file(&[
+            (b'H', &[0, 0, 0, 2, 0, 0, 0, 1, 2, 0xff]),
+            (b'P', &[0, 0, 0, 255, 255, 255, 9, 9, 9, 1]),
+            (b'C', "café".as_bytes()),
+            (b'D', &[0, 2]),
+        ]);
+        bytes.extend_from_slice(&[b'D', 0, 0]);
+
+        let report = validate(&bytes);
+        assert_eq!(
+            report.findings,
+            vec![
+                Finding::LongHeader {
+                    index: 0,
+                    length: 10
+                },
+                Finding::PartialPaletteColor {
+                    index: 1,
+                    length: 10
+                },
+                Finding::NonAsciiComment { index: 2 },
+                Finding::TrailingBytes {
+                    offset: 0x37,
+                    count: 3
+                },
+            ]
+        );
+        assert!(matches!(
+            report.error.as_ref().map(|e| e.root_cause()),
+            Some(MiniPngError::InvalidBlockLength)
+        ));
+
+        bytes.truncate(bytes.len() - 3);
+        let report = validate(&bytes);
+        assert!(report.is_valid());
+        assert_eq!(
+            report.findings.last(),
+            Some(&Finding::UnusedPaletteEntries { entries: vec![1] })
+        );
+    }
+
+    #[test]
+    fn test_validate_truncated_block_is_not_trailing_bytes() {
+        let mut bytes = file(&[(b'H', &[0, 0, 0, 8, 0, 0, 0, 2, 0])]);
+        // a data block declaring 2 bytes but only holding 1
+        bytes.extend_from_slice(&[b'D', 0, 0, 0, 2, 0xff]);
+
+        let report = validate(&bytes);
+        assert!(report.findings.is_empty());
+        assert!(matches!(
+            report.error.as_ref().map(|e| e.root_cause()),
+            Some(MiniPngError::BlockLengthMismatch {
+                declared: 2,
+                remaining: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_json() {
+        let report = validate(include_bytes!("../minipng-samples/bw/nok/missing-data.mp"));
+        assert_eq!(
+            report.to_json("missing \"data\".mp"),
+            "{\"path\":\"missing \\\"data\\\".mp\",\"valid\":false,\
+             \"error\":\"No data block found\",\"findings\":[]}"
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::Write;
+
+use crate::error::{MiniPngError, Result};
+use crate::mininpng::{Header, Palette};
+use crate::pixel_type::PixelType;
+
+/// Default maximum content size of the `D` blocks emitted by [`MiniPngWriter`].
+pub const DEFAULT_MAX_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Streaming Mini-PNG encoder.
+///
+/// The header, palette and comment blocks are written by
+/// [`MiniPngWriter::new`], then pixels are fed one row at a time with
+/// [`MiniPngWriter::write_row`] and split into `D` blocks of at most
+/// [`MiniPngWriter::with_max_block_size`] bytes. [`MiniPngWriter::finish`]
+/// must be called once every row has been written.
+///
+/// Each block is written with a few `write_all` calls, wrap the output in a
+/// `BufWriter` when it is a file or a socket.
+pub struct MiniPngWriter<W: Write> {
+    inner: W,
+    header: Header,
+    max_block_size: usize,
+    pending: Vec<u8>,
+    // black and white rows are not byte aligned, the bits that don't fill a
+    // whole byte yet wait here for the next row
+    bit_buffer: u8,
+    bit_count: u8,
+    rows_written: u32,
+    data_blocks: usize,
+}
+
+impl<W: Write> MiniPngWriter<W> {
+    /// Writes the magic number, the header, the palette and the comments.
+    ///
+    /// The palette must be given if and only if the pixel type is
+    /// [`PixelType::Palette`].
+    pub fn new(
+        mut inner: W,
+        header: &Header,
+        palette: Option<&Palette>,
+        comments: &[String],
+    ) -> Result<Self> {
+        match (header.pixel_type, palette) {
+            (PixelType::Palette, None) => return Err(MiniPngError::MissingPalette),
+            (PixelType::Palette, Some(_)) | (_, None) => {}
+            (_, Some(_)) => return Err(MiniPngError::UnexpectedPalette),
+        }
+        // rejects dimensions whose rows can't be addressed
+        header.data_size()?;
+
+        inner.write_all(b"Mini-PNG").map_err(MiniPngError::Io)?;
+
+        let mut content = Vec::with_capacity(9);
+        content.extend_from_slice(&header.width.to_be_bytes());
+        content.extend_from_slice(&header.height.to_be_bytes());
+        content.push(header.pixel_type as u8);
+        write_block(&mut inner, b'H', &content)?;
+
+        if let Some(palette) = palette {
+            write_block(&mut inner, b'P', palette.colors.as_flattened())?;
+        }
+
+        for comment in comments {
+            write_block(&mut inner, b'C', comment.as_bytes())?;
+        }
+
+        Ok(MiniPngWriter {
+            inner,
+            header: header.clone(),
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            pending: Vec::new(),
+            bit_buffer: 0,
+            bit_count: 0,
+            rows_written: 0,
+            data_blocks: 0,
+        })
+    }
+
+    /// Sets the maximum content size of the emitted `D` blocks.
+    pub fn with_max_block_size(mut self, max_block_size: usize) -> Self {
+        self.max_block_size = max_block_size.clamp(1, u32::MAX as usize);
+        self
+    }
+
+    /// Number of bytes expected by [`MiniPngWriter::write_row`].
+    ///
+    /// Black and white rows are packed 8 pixels per byte, most significant
+    /// bit first, and padded to a whole byte. Other pixel types use one byte
+    /// per pixel, or three for RGB.
+    pub fn row_size(&self) -> usize {
+        let width = self.header.width as usize;
+        match self.header.pixel_type {
+            PixelType::BlackAndWhite => width.div_ceil(8),
+            pixel_type => width * pixel_type.bit_size() / 8,
+        }
+    }
+
+    /// Appends the next row of pixels, see [`MiniPngWriter::row_size`].
+    pub fn write_row(&mut self, row: &[u8]) -> Result<()> {
+        if self.rows_written == self.header.height {
+            return Err(MiniPngError::RowCountMismatch {
+                expected: self.header.height,
+                found: self.rows_written as u64 + 1,
+            });
+        }
+        if row.len() != self.row_size() {
+            return Err(MiniPngError::RowLengthMismatch {
+                expected: self.row_size(),
+                found: row.len(),
+            });
+        }
+
+        match self.header.pixel_type {
+            PixelType::BlackAndWhite => {
+                for x in 0..self.header.width as usize {
+                    self.push_bit((row[x / 8] >> (7 - (x % 8))) & 1);
+                }
+            }
+            _ => self.pending.extend_from_slice(row),
+        }
+        self.rows_written += 1;
+
+        while self.pending.len() >= self.max_block_size {
+            let rest = self.pending.split_off(self.max_block_size);
+            self.emit_data_block()?;
+            self.pending = rest;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the remaining pixels and returns the underlying writer.
+    ///
+    /// Fails with [`MiniPngError::RowCountMismatch`] if fewer than `height`
+    /// rows were written.
+    pub fn finish(mut self) -> Result<W> {
+        if self.rows_written != self.header.height {
+            return Err(MiniPngError::RowCountMismatch {
+                expected: self.header.height,
+                found: self.rows_written as u64,
+            });
+        }
+
+        if self.bit_count > 0 {
+            self.pending.push(self.bit_buffer);
+            self.bit_count = 0;
+        }
+        if !self.pending.is_empty() || self.data_blocks == 0 {
+            self.emit_data_block()?;
+        }
+
+        self.inner.flush().map_err(MiniPngError::Io)?;
+        Ok(self.inner)
+    }
+
+    fn push_bit(&mut self, bit: u8) {
+        self.bit_buffer |= bit << (7 - self.bit_count);
+        self.bit_count += 1;
+
+        if self.bit_count == 8 {
+            self.pending.push(self.bit_buffer);
+            self.bit_buffer = 0;
+            self.bit_count = 0;
+        }
+    }
+
+    fn emit_data_block(&mut self) -> Result<()> {
+        write_block(&mut self.inner, b'D', &self.pending)?;
+        self.pending.clear();
+        self.data_blocks += 1;
+        Ok(())
+    }
+}
+
+fn write_block(writer: &mut impl Write, block_type: u8, content: &[u8]) -> Result<()> {
+    let length =
+        u32::try_from(content.len()).map_err(|_| MiniPngError::BlockTooLarge(content.len()))?;
+
+    writer.write_all(&[block_type]).map_err(MiniPngError::Io)?;
+    writer
+        .write_all(&length.to_be_bytes())
+        .map_err(MiniPngError::Io)?;
+    writer.write_all(content).map_err(MiniPngError::Io)
+}
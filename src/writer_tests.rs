@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::header;
+    use crate::{Block, MiniPNG, MiniPngError, MiniPngReader, MiniPngWriter, PixelType};
+
+    #[test]
+    fn test_writer_packs_black_and_white_rows() {
+        let mut writer = MiniPngWriter::new(
+            Vec::new(),
+            &header(10, 3, PixelType::BlackAndWhite),
+            None,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(writer.row_size(), 2);
+
+        writer.write_row(&[0xff, 0xc0]).unwrap();
+        writer.write_row(&[0x00, 0x00]).unwrap();
+        writer.write_row(&[0xff, 0xff]).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let image: MiniPNG = bytes.try_into().unwrap();
+        let expected = MiniPNG::bw_from_string("XXXXXXXXXX\n          \nXXXXXXXXXX").unwrap();
+        assert_eq!(image.data, expected.data);
+    }
+
+    #[test]
+    fn test_writer_splits_data_blocks() {
+        let mut writer = MiniPngWriter::new(
+            Vec::new(),
+            &header(4, 5, PixelType::RGB),
+            None,
+            &["split".to_string()],
+        )
+        .unwrap()
+        .with_max_block_size(25);
+
+        for y in 0..5u8 {
+            writer.write_row(&[y; 12]).unwrap();
+        }
+        let bytes = writer.finish().unwrap();
+
+        let sizes: Vec<usize> = MiniPngReader::new(bytes.as_slice())
+            .unwrap()
+            .filter_map(|block| match block.unwrap() {
+                Block::Data(data) => Some(data.len()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(sizes, vec![25, 25, 10]);
+
+        let image = MiniPNG::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(image.comments, vec!["split".to_string()]);
+        assert_eq!(&image.data[12..24], &[1; 12]);
+    }
+
+    #[test]
+    fn test_writer_row_count_checks() {
+        let header = header(2, 2, PixelType::GrayLevels);
+
+        let mut writer = MiniPngWriter::new(Vec::new(), &header, None, &[]).unwrap();
+        writer.write_row(&[1, 2]).unwrap();
+        assert!(matches!(
+            writer.finish(),
+            Err(MiniPngError::RowCountMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+
+        let mut writer = MiniPngWriter::new(Vec::new(), &header, None, &[]).unwrap();
+        assert!(matches!(
+            writer.write_row(&[1, 2, 3]),
+            Err(MiniPngError::RowLengthMismatch {
+                expected: 2,
+                found: 3
+            })
+        ));
+        writer.write_row(&[1, 2]).unwrap();
+        writer.write_row(&[3, 4]).unwrap();
+        assert!(matches!(
+            writer.write_row(&[5, 6]),
+            Err(MiniPngError::RowCountMismatch {
+                expected: 2,
+                found: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_to_round_trips_palette() {
+        let bytes = include_bytes!("../minipng-samples/other/ok/german-flag-palette.mp");
+        let image = MiniPNG::read_from(&bytes[..]).unwrap();
+
+        let written = image.write_to(Vec::new()).unwrap();
+        let reread = MiniPNG::read_from(written.as_slice()).unwrap();
+
+        assert_eq!(reread.header, image.header);
+        assert_eq!(reread.palette, image.palette);
+        assert_eq!(reread.data, image.data);
+    }
+}
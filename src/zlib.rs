@@ -0,0 +1,513 @@
+// zlib (RFC 1950) and deflate (RFC 1951), enough for PNG files: the
+// compressor writes stored and fixed Huffman blocks with LZ77 matching, the
+// decompressor reads every block type.
+
+use crate::error::{MiniPngError, Result};
+
+// base length and number of extra bits of the length codes 257 to 285
+pub(crate) const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+pub(crate) const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+// base distance and number of extra bits of the distance codes 0 to 29
+pub(crate) const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+pub(crate) const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+// number of previous positions tried for each match, trades speed for ratio
+const MAX_CHAIN: usize = 64;
+const HASH_SIZE: usize = 1 << 15;
+const MAX_STORED_BLOCK: usize = 65535;
+
+// Adler-32 checksum of `data`, as stored at the end of a zlib stream
+pub(crate) fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // 5552 bytes is the most that can be summed before `b` overflows
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+// compresses `data` into a zlib stream, using fixed Huffman codes unless
+// storing the data uncompressed is smaller
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    // deflate with a 32K window, no preset dictionary, default level
+    let mut output = vec![0x78, 0x9c];
+
+    let fixed = deflate_fixed(data);
+    if fixed.len() < data.len() + data.len().div_ceil(MAX_STORED_BLOCK).max(1) * 5 {
+        output.extend(fixed);
+    } else {
+        output.extend(deflate_stored(data));
+    }
+
+    output.extend(adler32(data).to_be_bytes());
+    output
+}
+
+// writes bits starting from the least significant bit of each byte
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+fn deflate_stored(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(data.len() + 5);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+
+    if chunks.peek().is_none() {
+        // an empty input still needs a final block
+        output.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        // BFINAL and BTYPE = 00, padded to a byte boundary
+        output.push(last as u8);
+        output.extend((chunk.len() as u16).to_le_bytes());
+        output.extend((!(chunk.len() as u16)).to_le_bytes());
+        output.extend(chunk);
+    }
+    output
+}
+
+fn deflate_fixed(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    // BFINAL = 1, BTYPE = 01
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    // most recent position of each hash, and the position before it with
+    // the same hash, for the last WINDOW_SIZE positions
+    let mut head = vec![usize::MAX; HASH_SIZE];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &previous);
+        let advance = if length >= MIN_MATCH {
+            write_match(&mut writer, length, distance);
+            length
+        } else {
+            write_symbol(&mut writer, data[pos] as u16);
+            1
+        };
+
+        for inserted in pos..pos + advance {
+            if inserted + MIN_MATCH <= data.len() {
+                let h = hash(data, inserted);
+                previous[inserted % WINDOW_SIZE] = head[h];
+                head[h] = inserted;
+            }
+        }
+        pos += advance;
+    }
+
+    // end of block
+    write_symbol(&mut writer, 256);
+    writer.finish()
+}
+
+fn hash(data: &[u8], pos: usize) -> usize {
+    let value = u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], 0]);
+    (value.wrapping_mul(2654435761) >> 17) as usize % HASH_SIZE
+}
+
+// longest earlier occurrence of the bytes at `pos`, as (length, distance)
+fn longest_match(data: &[u8], pos: usize, head: &[usize], previous: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let max_length = (data.len() - pos).min(MAX_MATCH);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(data, pos)];
+
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW_SIZE {
+            break;
+        }
+
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max_length])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best.0 {
+            best = (length, pos - candidate);
+            if length == max_length {
+                break;
+            }
+        }
+
+        // entries of `previous` are overwritten once out of the window, which
+        // shows up as a position that is not before the current one
+        let next = previous[candidate % WINDOW_SIZE];
+        if next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+
+    best
+}
+
+// literals and lengths with the fixed Huffman code of RFC 1951 section 3.2.6
+fn write_symbol(writer: &mut BitWriter, symbol: u16) {
+    let symbol = symbol as u32;
+    let (code, bits) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xc0 + symbol - 280, 8),
+    };
+    writer.write_code(code, bits);
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize) {
+    let code = LENGTH_BASE
+        .iter()
+        .rposition(|&base| base as usize <= length)
+        .unwrap_or(0);
+    write_symbol(writer, 257 + code as u16);
+    writer.write(
+        (length - LENGTH_BASE[code] as usize) as u32,
+        LENGTH_EXTRA[code] as u32,
+    );
+
+    // distance codes are all 5 bits long in fixed Huffman blocks
+    let code = DISTANCE_BASE
+        .iter()
+        .rposition(|&base| base as usize <= distance)
+        .unwrap_or(0);
+    writer.write_code(code as u32, 5);
+    writer.write(
+        (distance - DISTANCE_BASE[code] as usize) as u32,
+        DISTANCE_EXTRA[code] as u32,
+    );
+}
+
+// decompresses a zlib stream, failing if it inflates to more than
+// `max_size` bytes
+pub(crate) fn decompress(data: &[u8], max_size: usize) -> Result<Vec<u8>> {
+    let [cmf, flg, ..] = *data else {
+        return Err(MiniPngError::InvalidDeflate("truncated zlib header"));
+    };
+    if cmf & 0x0f != 8 || cmf >> 4 > 7 {
+        return Err(MiniPngError::InvalidDeflate(
+            "compression method is not deflate",
+        ));
+    }
+    if u16::from_be_bytes([cmf, flg]) % 31 != 0 {
+        return Err(MiniPngError::InvalidDeflate("invalid zlib header check"));
+    }
+    if flg & 0x20 != 0 {
+        return Err(MiniPngError::InvalidDeflate(
+            "preset dictionaries are not supported",
+        ));
+    }
+
+    let mut reader = BitReader {
+        data,
+        pos: 2,
+        buffer: 0,
+        count: 0,
+    };
+    let output = inflate(&mut reader, max_size)?;
+
+    // the checksum starts at the next byte boundary
+    let pos = reader.pos - (reader.count / 8) as usize;
+    let checksum = data
+        .get(pos..pos + 4)
+        .ok_or(MiniPngError::InvalidDeflate("missing Adler-32 checksum"))?;
+    if adler32(&output).to_be_bytes() != checksum {
+        return Err(MiniPngError::InvalidDeflate("Adler-32 checksum mismatch"));
+    }
+
+    Ok(output)
+}
+
+// reads bits starting from the least significant bit of each byte
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, bits: u32) -> Result<u32> {
+        while self.count < bits {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or(MiniPngError::InvalidDeflate("truncated deflate stream"))?;
+            self.buffer |= (byte as u64) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+
+        let value = (self.buffer & ((1u64 << bits) - 1)) as u32;
+        self.buffer >>= bits;
+        self.count -= bits;
+        Ok(value)
+    }
+
+    // drops the bits left in the current byte
+    fn align(&mut self) {
+        let extra = self.count % 8;
+        self.buffer >>= extra;
+        self.count -= extra;
+    }
+}
+
+// canonical Huffman code, as the number of codes of each length and the
+// symbols sorted by code
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // more codes of a length than the previous lengths leave room for
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = left * 2 - count as i32;
+            if left < 0 {
+                return Err(MiniPngError::InvalidDeflate("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16> {
+        // `code` is the code read so far, `first` the first code of that
+        // length and `index` the position of that code in `symbols`
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(MiniPngError::InvalidDeflate("invalid Huffman code"))
+    }
+}
+
+fn inflate(reader: &mut BitReader, max_size: usize) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let length = reader.bits(16)? as u16;
+                let complement = reader.bits(16)? as u16;
+                if length != !complement {
+                    return Err(MiniPngError::InvalidDeflate("stored block length mismatch"));
+                }
+                if output.len() + length as usize > max_size {
+                    return Err(MiniPngError::InvalidDeflate("decompressed data too large"));
+                }
+                for _ in 0..length {
+                    output.push(reader.bits(8)? as u8);
+                }
+            }
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                inflate_block(reader, &literals, &distances, &mut output, max_size)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                inflate_block(reader, &literals, &distances, &mut output, max_size)?;
+            }
+            _ => return Err(MiniPngError::InvalidDeflate("invalid block type")),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman)> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+// order in which the code length code lengths are stored
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman)> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(MiniPngError::InvalidDeflate(
+            "too many length or distance codes",
+        ));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[i] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or(MiniPngError::InvalidDeflate(
+                    "repeated length without a previous one",
+                ))?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        if lengths.len() + repeat as usize > literal_count + distance_count {
+            return Err(MiniPngError::InvalidDeflate("too many code lengths"));
+        }
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths[256] == 0 {
+        return Err(MiniPngError::InvalidDeflate("missing end of block code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    literals: &Huffman,
+    distances: &Huffman,
+    output: &mut Vec<u8>,
+    max_size: usize,
+) -> Result<()> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        let length = match symbol {
+            0..=255 => {
+                if output.len() >= max_size {
+                    return Err(MiniPngError::InvalidDeflate("decompressed data too large"));
+                }
+                output.push(symbol as u8);
+                continue;
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                LENGTH_BASE[code] as usize + reader.bits(LENGTH_EXTRA[code] as u32)? as usize
+            }
+            _ => return Err(MiniPngError::InvalidDeflate("invalid length code")),
+        };
+
+        let code = distances.decode(reader)? as usize;
+        if code >= 30 {
+            return Err(MiniPngError::InvalidDeflate("invalid distance code"));
+        }
+        let distance =
+            DISTANCE_BASE[code] as usize + reader.bits(DISTANCE_EXTRA[code] as u32)? as usize;
+        if distance > output.len() {
+            return Err(MiniPngError::InvalidDeflate(
+                "distance before the start of the data",
+            ));
+        }
+        if output.len() + length > max_size {
+            return Err(MiniPngError::InvalidDeflate("decompressed data too large"));
+        }
+
+        // the copy may overlap the bytes it produces
+        let start = output.len() - distance;
+        for i in 0..length {
+            output.push(output[start + i]);
+        }
+    }
+}
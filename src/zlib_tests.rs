@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use crate::MiniPngError;
+    use crate::zlib::{adler32, compress, decompress};
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+        // large enough to need the modulo between chunks
+        assert_eq!(adler32(&[0xff; 100_000]), 0x149a302c);
+    }
+
+    #[test]
+    fn test_compress_framing() {
+        let data = b"Mini-PNG Mini-PNG Mini-PNG";
+        let compressed = compress(data);
+
+        // deflate with a 32K window and a valid header check
+        assert_eq!(compressed[0], 0x78);
+        assert_eq!(u16::from_be_bytes([compressed[0], compressed[1]]) % 31, 0);
+        assert_eq!(
+            compressed[compressed.len() - 4..],
+            adler32(data).to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_compress_uses_matches() {
+        let data = vec![42u8; 100_000];
+        assert!(compress(&data).len() < 1000);
+    }
+
+    #[test]
+    fn test_compress_falls_back_to_stored() {
+        // random bytes above 143 take 9 bits with fixed codes and have no
+        // matches, storing them is smaller
+        let mut state = 1u32;
+        let data: Vec<u8> = (0..70_000)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                144 + ((state >> 16) % 112) as u8
+            })
+            .collect();
+        let compressed = compress(&data);
+        // two stored blocks of 5 bytes of framing, plus zlib header and checksum
+        assert_eq!(compressed.len(), data.len() + 2 * 5 + 6);
+        assert_eq!(compressed[2], 0);
+    }
+
+    #[test]
+    fn test_compress_empty() {
+        let compressed = compress(b"");
+        assert_eq!(compressed[compressed.len() - 4..], [0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_decompress_round_trips() {
+        let mut state = 1u32;
+        let random: Vec<u8> = (0..70_000)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect();
+
+        for data in [
+            Vec::new(),
+            b"Mini-PNG Mini-PNG Mini-PNG".to_vec(),
+            vec![42u8; 100_000],
+            random,
+        ] {
+            assert_eq!(decompress(&compress(&data), data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_decompress_dynamic_huffman() {
+        // zlib.compress(bytes((i * i // 7) % 26 + 97 for i in range(400)), 9)
+        let compressed = [
+            0x78, 0xda, 0xdd, 0xce, 0x8b, 0x15, 0x04, 0x11, 0x0c, 0x40, 0xd1, 0x5a, 0x11, 0x64,
+            0x88, 0x4f, 0x90, 0x21, 0xaa, 0xdf, 0xdd, 0x36, 0xf6, 0x16, 0xf0, 0xce, 0x33, 0xc6,
+            0x58, 0x07, 0x01, 0x13, 0xb5, 0x21, 0xea, 0x62, 0xee, 0x72, 0x03, 0x8d, 0xe3, 0x69,
+            0x9a, 0x87, 0x15, 0xf9, 0xa6, 0x05, 0x55, 0x93, 0xc4, 0xe9, 0x19, 0x18, 0x46, 0x58,
+            0xb8, 0xc9, 0x70, 0xdc, 0x05, 0x84, 0xe0, 0xad, 0x41, 0x47, 0xf6, 0x3a, 0x0b, 0xba,
+            0xb3, 0x3a, 0xa1, 0xb7, 0xba, 0x65, 0x72, 0x6f, 0xf5, 0xa7, 0x75, 0x9e, 0xb2, 0xd5,
+            0x7a, 0xa4, 0xbe, 0x8e, 0xc3, 0x32, 0xd5, 0xe7, 0xa1, 0xa1, 0xbe, 0x40, 0x02, 0x65,
+            0x47, 0x36, 0xb4, 0x71, 0x85, 0xf1, 0x2d, 0xb3, 0x9f, 0x51, 0x92, 0x56, 0x58, 0xe9,
+            0x32, 0x2a, 0x3f, 0x66, 0x92, 0x3f, 0x83, 0xc2, 0x95, 0x9e, 0xa3, 0x53, 0x19, 0x8d,
+            0x12, 0x06, 0x70, 0xd6, 0xfc, 0xfc, 0xe9, 0xf5, 0x07, 0x9f, 0x2e, 0xaa, 0x83,
+        ];
+        let expected: Vec<u8> = (0..400u32).map(|i| ((i * i / 7) % 26 + 97) as u8).collect();
+        assert_eq!(decompress(&compressed, 400).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let data = vec![7u8; 1000];
+        let compressed = compress(&data);
+
+        // inflating past the expected size is stopped
+        assert!(matches!(
+            decompress(&compressed, 999),
+            Err(MiniPngError::InvalidDeflate(_))
+        ));
+
+        let mut corrupted = compressed.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            decompress(&corrupted, 1000),
+            Err(MiniPngError::InvalidDeflate(_))
+        ));
+
+        for invalid in [
+            &compressed[..compressed.len() - 5],
+            &[0x78, 0x9d, 0x03, 0x00],
+            &[0x79, 0x9c, 0x03, 0x00],
+            // reserved block type 11
+            &[0x78, 0x9c, 0x07, 0x00],
+        ] {
+            assert!(matches!(
+                decompress(invalid, 1000),
+                Err(MiniPngError::InvalidDeflate(_))
+            ));
+        }
+    }
+}